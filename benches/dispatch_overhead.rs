@@ -0,0 +1,99 @@
+//! Compares polling union futures of 2, 4 and 8 variants to polling the same futures boxed as
+//! trait objects.
+
+#[macro_use]
+extern crate criterion;
+extern crate futures;
+#[macro_use]
+extern crate union_future;
+
+use criterion::{black_box, BenchmarkId, Criterion};
+use futures::{Async, Future, Poll};
+
+// the number of futures polled by an iteration, cycling through the variants
+const FUTURES: usize = 64;
+
+// a future which is always ready, so it can be polled repeatedly
+pub struct Value<const N: u64>(u64);
+
+impl<const N: u64> Future for Value<N> {
+    type Item = u64;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<u64, ()> {
+        Ok(Async::Ready(self.0 + N))
+    }
+}
+
+union_future!(Union2<u64, ()>,
+        V0 => Value<0>,
+        V1 => Value<1>);
+
+union_future!(Union4<u64, ()>,
+        V0 => Value<0>,
+        V1 => Value<1>,
+        V2 => Value<2>,
+        V3 => Value<3>);
+
+union_future!(Union8<u64, ()>,
+        V0 => Value<0>,
+        V1 => Value<1>,
+        V2 => Value<2>,
+        V3 => Value<3>,
+        V4 => Value<4>,
+        V5 => Value<5>,
+        V6 => Value<6>,
+        V7 => Value<7>);
+
+type Boxed = Box<dyn Future<Item = u64, Error = ()>>;
+
+// builds the futures of `$n` variants, where `$f` converts the future of a variant into a union
+// future or a boxed future
+macro_rules! futures {
+    ($n:expr, $f:ident, [$($v:tt),*]) => (
+        (0..FUTURES)
+            .map(|i| match i % $n {
+                $( $v => $f!(Value::<$v>(i as u64)), )*
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>()
+    );
+}
+
+macro_rules! into {
+    ($e:expr) => ($e.into());
+}
+
+macro_rules! boxed {
+    ($e:expr) => (Box::new($e) as Boxed);
+}
+
+fn poll_all<F: Future>(futures: &mut [F]) {
+    for f in futures {
+        black_box(f.poll()).ok();
+    }
+}
+
+fn dispatch_overhead(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dispatch_overhead");
+
+    let mut union2: Vec<Union2> = futures!(2, into, [0, 1]);
+    let mut boxed2: Vec<Boxed> = futures!(2, boxed, [0, 1]);
+    group.bench_function(BenchmarkId::new("union", 2), |b| b.iter(|| poll_all(&mut union2)));
+    group.bench_function(BenchmarkId::new("boxed", 2), |b| b.iter(|| poll_all(&mut boxed2)));
+
+    let mut union4: Vec<Union4> = futures!(4, into, [0, 1, 2, 3]);
+    let mut boxed4: Vec<Boxed> = futures!(4, boxed, [0, 1, 2, 3]);
+    group.bench_function(BenchmarkId::new("union", 4), |b| b.iter(|| poll_all(&mut union4)));
+    group.bench_function(BenchmarkId::new("boxed", 4), |b| b.iter(|| poll_all(&mut boxed4)));
+
+    let mut union8: Vec<Union8> = futures!(8, into, [0, 1, 2, 3, 4, 5, 6, 7]);
+    let mut boxed8: Vec<Boxed> = futures!(8, boxed, [0, 1, 2, 3, 4, 5, 6, 7]);
+    group.bench_function(BenchmarkId::new("union", 8), |b| b.iter(|| poll_all(&mut union8)));
+    group.bench_function(BenchmarkId::new("boxed", 8), |b| b.iter(|| poll_all(&mut boxed8)));
+
+    group.finish();
+}
+
+criterion_group!(benches, dispatch_overhead);
+criterion_main!(benches);
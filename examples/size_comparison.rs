@@ -0,0 +1,96 @@
+//! The same branching query written with a union future and with a boxed future, to compare the
+//! size of the binaries.
+//!
+//! The union future is used by default, and the boxed future when the example is built with
+//! `RUSTFLAGS='--cfg size_comparison="boxed"'`.
+
+extern crate futures;
+#[macro_use]
+extern crate union_future;
+
+use futures::future::{ok, FutureResult};
+use futures::{Async, Future, Poll};
+use std::collections::HashMap;
+use std::env;
+
+pub struct DbError;
+
+// a lookup which is ready after it has been polled as many times as the length of its key
+pub struct DbLookup {
+    key: String,
+    polls: usize,
+}
+
+impl Future for DbLookup {
+    type Item = u64;
+    type Error = DbError;
+
+    fn poll(&mut self) -> Poll<u64, DbError> {
+        self.polls += 1;
+        if self.polls > self.key.len() {
+            Ok(Async::Ready(self.key.bytes().map(u64::from).sum()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+fn lookup(key: &str) -> DbLookup {
+    DbLookup { key: key.to_owned(), polls: 0 }
+}
+
+#[cfg_attr(size_comparison = "boxed", allow(dead_code))]
+mod union {
+    use super::*;
+
+    union_future!(QueryFuture<u64, DbError>,
+            Cached => FutureResult<u64, DbError>,
+            Db => DbLookup);
+
+    pub fn query(cache: &HashMap<String, u64>, key: &str) -> QueryFuture {
+        match cache.get(key) {
+            Some(&val) => ok(val).into(),
+            None => lookup(key).into(),
+        }
+    }
+}
+
+#[cfg_attr(not(size_comparison = "boxed"), allow(dead_code))]
+mod boxed {
+    use super::*;
+
+    pub type QueryFuture = Box<dyn Future<Item = u64, Error = DbError>>;
+
+    pub fn query(cache: &HashMap<String, u64>, key: &str) -> QueryFuture {
+        match cache.get(key) {
+            Some(&val) => Box::new(ok(val)),
+            None => Box::new(lookup(key)),
+        }
+    }
+}
+
+#[cfg(not(size_comparison = "boxed"))]
+use union::query;
+#[cfg(size_comparison = "boxed")]
+use boxed::query;
+
+fn main() {
+    let mut cache = HashMap::new();
+    cache.insert("answer".to_owned(), 42);
+    for key in env::args().skip(1) {
+        let mut f = query(&cache, &key);
+        loop {
+            match f.poll() {
+                Ok(Async::Ready(val)) => {
+                    println!("{}: {}", key, val);
+                    break;
+                }
+                Ok(Async::NotReady) => {}
+                Err(DbError) => {
+                    println!("{}: failed", key);
+                    break;
+                }
+            }
+        }
+    }
+}
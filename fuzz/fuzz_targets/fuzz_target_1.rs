@@ -0,0 +1,136 @@
+//! Expands `union_future!` over random variant names and types.
+//!
+//! `macro_rules` macros are only expanded by the compiler, so each input is rendered into a crate
+//! invoking `union_future!`, which is checked with cargo. The input may be rejected with any
+//! compile error, such as for a type that isn't a future or a path that doesn't resolve, but the
+//! compiler must not panic while expanding the macro.
+
+#![no_main]
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+use libfuzzer_sys::fuzz_target;
+use std::fmt::{self, Write};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::OnceLock;
+
+const FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_";
+const REST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_0123456789";
+
+#[derive(Debug)]
+struct Ident(String);
+
+impl<'a> Arbitrary<'a> for Ident {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut ident = String::new();
+        ident.push(*u.choose(FIRST)? as char);
+        for _ in 0..u.int_in_range(0..=7)? {
+            ident.push(*u.choose(REST)? as char);
+        }
+        Ok(Ident(ident))
+    }
+}
+
+impl fmt::Display for Ident {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+enum Num {
+    U8,
+    U32,
+    U64,
+}
+
+impl fmt::Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Num::U8 => "u8",
+            Num::U32 => "u32",
+            Num::U64 => "u64",
+        })
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+enum Type {
+    Result(Num),
+    Empty(Num),
+    Boxed,
+    NotAFuture(Num),
+    Path(Vec<Ident>, Ident),
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Type::Result(ref n) => write!(f, "FutureResult<{}, Error>", n),
+            Type::Empty(ref n) => write!(f, "::futures::future::Empty<{}, Error>", n),
+            Type::Boxed => f.write_str("Box<Future<Item = u64, Error = Error>>"),
+            Type::NotAFuture(ref n) => write!(f, "{}", n),
+            Type::Path(ref modules, ref ty) => {
+                for m in modules {
+                    write!(f, "{}::", m)?;
+                }
+                write!(f, "{}", ty)
+            }
+        }
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    name: Ident,
+    variants: Vec<(Ident, Type)>,
+    trailing_comma: bool,
+}
+
+impl Input {
+    fn render(&self) -> String {
+        let mut src = String::from("#[macro_use]\nextern crate union_future;\nextern crate futures;\n\
+                                    use futures::future::*;\npub enum Error {}\n");
+        write!(src, "union_future!({}<u64, Error>", self.name).unwrap();
+        for (name, ty) in &self.variants {
+            write!(src, ",\n    {} => {}", name, ty).unwrap();
+        }
+        if self.trailing_comma {
+            src.push(',');
+        }
+        src.push_str(");\n");
+        src
+    }
+}
+
+// the crate checked for each input, which depends on union-future of this repository
+fn scratch() -> &'static PathBuf {
+    static SCRATCH: OnceLock<PathBuf> = OnceLock::new();
+    SCRATCH.get_or_init(|| {
+        let dir = std::env::temp_dir().join("union-future-fuzz");
+        fs::create_dir_all(dir.join("src")).unwrap();
+        let manifest = format!("[package]\nname = \"union-future-fuzz-input\"\nversion = \"0.0.0\"\n\n\
+                                [dependencies]\nfutures = \"0.1.7\"\n\
+                                union-future = {{ path = {:?} }}\n\n[workspace]\n",
+                               concat!(env!("CARGO_MANIFEST_DIR"), "/.."));
+        fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+        dir
+    })
+}
+
+fuzz_target!(|input: Input| {
+    let src = input.render();
+    let dir = scratch();
+    fs::write(dir.join("src/lib.rs"), &src).unwrap();
+    let output = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned()))
+        .args(["check", "--quiet", "--message-format", "short"])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() &&
+       (stderr.contains("internal compiler error") || stderr.contains("unexpectedly panicked")) {
+        panic!("the compiler panicked expanding\n{}\n{}", src, stderr);
+    }
+});
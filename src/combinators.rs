@@ -0,0 +1,1072 @@
+// Combinators of the futures 0.1 enums generated by `union_future`. The enums implement the
+// `UnionFuture` marker trait, which gives them the methods of `UnionFutureExt` through a blanket
+// impl. The methods return the concrete futures defined in this module, and accept the same
+// closures as the methods of `futures::Future` with the same name, such as a closure returning a
+// `Result` for `then`.
+
+use std::any::Any;
+use std::fmt;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+
+use futures::{Async, Future, IntoFuture, Poll};
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_combinators {
+    ($name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]) => (
+        #[allow(deprecated)]
+        impl<$($g)*> $crate::UnionFuture for $name<$($u)*> where $($w)* {}
+    );
+}
+
+/// A marker trait implemented by the enums generated by `union_future`, which gives them the
+/// combinators of `UnionFutureExt`.
+pub trait UnionFuture: Future {}
+
+/// Combinators of the enums generated by `union_future`, which return the futures of this crate
+/// and keep the concrete type of the enum without boxing.
+///
+/// The methods are only available when this trait is imported, so they never replace the
+/// methods of `futures::Future` with the same name, such as `map_err` or `and_then`. When both
+/// traits are imported, such a method is called as `UnionFutureExt::map_err(future, f)`.
+///
+/// ```
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// use futures::Future;
+/// use futures::future::*;
+/// use union_future::UnionFutureExt;
+///
+/// # pub struct DbError;
+/// union_future!(QueryFuture<u64, DbError>,
+///       Cached => FutureResult<u64, DbError>,
+///       Db => Empty<u64, DbError>);
+///
+/// # fn main() {
+/// let f: QueryFuture = ok(5).into();
+/// let doubled = f.map_ok(|n| n * 2);
+/// let f: QueryFuture = ok(5).into();
+/// let described = UnionFutureExt::map_err(f, |_| "query failed");
+/// # }
+/// ```
+pub trait UnionFutureExt: UnionFuture + Sized {
+    /// Maps the item of this future with the closure `f`, keeping its error.
+    fn map_ok<U, F>(self, f: F) -> MapOk<Self, F>
+        where F: FnOnce(Self::Item) -> U
+    {
+        MapOk::new(self, f)
+    }
+
+    /// Maps the error of this future with the closure `f`, keeping its item.
+    fn map_err<E, F>(self, f: F) -> MapErr<Self, F>
+        where F: FnOnce(Self::Error) -> E
+    {
+        MapErr::new(self, f)
+    }
+
+    /// Calls the closure `f` with a reference to the item of this future before returning it.
+    fn inspect<F>(self, f: F) -> Inspect<Self, F>
+        where F: FnOnce(&Self::Item)
+    {
+        Inspect::new(self, f)
+    }
+
+    /// Calls the closure `f` with a reference to the error of this future before returning it.
+    fn inspect_err<F>(self, f: F) -> InspectErr<Self, F>
+        where F: FnMut(&Self::Error)
+    {
+        InspectErr::new(self, f)
+    }
+
+    /// Chains the future returned by the closure `f`, which is called with the result of this
+    /// future.
+    fn then<B, F>(self, f: F) -> Then<Self, B, F>
+        where B: IntoFuture,
+              F: FnOnce(Result<Self::Item, Self::Error>) -> B
+    {
+        Then::new(self, f)
+    }
+
+    /// Flattens this future resolving to a future into the future it resolves to, converting
+    /// the error of this future with the `From` trait.
+    fn flatten(self) -> Flatten<Self>
+        where Self::Item: IntoFuture,
+              <Self::Item as IntoFuture>::Error: From<Self::Error>
+    {
+        Flatten::new(self)
+    }
+
+    /// Chains the future returned by the closure `f`, which is called with the item of this
+    /// future, while an error of this future is returned as is.
+    fn and_then<B, F>(self, f: F) -> AndThen<Self, B, F>
+        where B: IntoFuture<Error = Self::Error>,
+              F: FnOnce(Self::Item) -> B
+    {
+        AndThen::new(self, f)
+    }
+
+    /// Chains the future returned by the closure `f`, which is called with the error of this
+    /// future, while an item of this future is returned as is.
+    fn or_else<B, F>(self, f: F) -> OrElse<Self, B, F>
+        where B: IntoFuture<Item = Self::Item>,
+              F: FnOnce(Self::Error) -> B
+    {
+        OrElse::new(self, f)
+    }
+
+    /// Polls this future and `other` concurrently, resolving to both of their items or to the
+    /// first error.
+    fn zip<B>(self, other: B) -> Zip<Self, B>
+        where B: Future<Error = Self::Error>
+    {
+        Zip::new(self, other)
+    }
+
+    /// Polls this future and `other` concurrently, resolving to the result of the first one to
+    /// resolve and dropping the other one.
+    fn race<B>(self, other: B) -> Race<Self, B>
+        where B: Future<Item = Self::Item, Error = Self::Error>
+    {
+        Race::new(self, other)
+    }
+
+    /// Converts the item of this future with the `From` trait.
+    fn ok_into<U>(self) -> OkInto<Self, U>
+        where U: From<Self::Item>
+    {
+        OkInto::new(self)
+    }
+
+    /// Converts the error of this future with the `From` trait.
+    fn err_into<E>(self) -> ErrInto<Self, E>
+        where E: From<Self::Error>
+    {
+        ErrInto::new(self)
+    }
+
+    /// Converts the error of this future with the `From` trait, like the method of
+    /// `futures::Future` with the same name.
+    #[allow(clippy::wrong_self_convention)]
+    fn from_err<E>(self) -> FromErr<Self, E>
+        where E: From<Self::Error>
+    {
+        FromErr::new(self)
+    }
+
+    /// Catches a panic while polling this future, which is converted into its error with the
+    /// `From` trait.
+    fn catch_panic(self) -> CatchPanic<Self>
+        where Self::Error: From<Box<dyn Any + Send>>
+    {
+        CatchPanic::new(self)
+    }
+
+    /// Panics when this future is polled after it has resolved, in builds with debug assertions.
+    fn once(self) -> OnceFuture<Self> {
+        OnceFuture::new(self)
+    }
+}
+
+impl<T: UnionFuture> UnionFutureExt for T {}
+
+/// Future for the `map_ok` method of `UnionFutureExt`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct MapOk<A, F> {
+    future: A,
+    f: Option<F>,
+}
+
+impl<A, F> MapOk<A, F> {
+    fn new(future: A, f: F) -> MapOk<A, F> {
+        MapOk { future, f: Some(f) }
+    }
+}
+
+impl<A, F, U> Future for MapOk<A, F>
+    where A: Future,
+          F: FnOnce(A::Item) -> U
+{
+    type Item = U;
+    type Error = A::Error;
+
+    fn poll(&mut self) -> Poll<U, A::Error> {
+        let item = match self.future.poll()? {
+            Async::Ready(item) => item,
+            Async::NotReady => return Ok(Async::NotReady),
+        };
+        let f = self.f.take().expect("cannot poll MapOk twice");
+        Ok(Async::Ready(f(item)))
+    }
+}
+
+/// Future for the `map_err` method of `UnionFutureExt`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct MapErr<A, F> {
+    future: A,
+    f: Option<F>,
+}
+
+impl<A, F> MapErr<A, F> {
+    fn new(future: A, f: F) -> MapErr<A, F> {
+        MapErr { future, f: Some(f) }
+    }
+}
+
+impl<A, F, E> Future for MapErr<A, F>
+    where A: Future,
+          F: FnOnce(A::Error) -> E
+{
+    type Item = A::Item;
+    type Error = E;
+
+    fn poll(&mut self) -> Poll<A::Item, E> {
+        match self.future.poll() {
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Ok(Async::Ready(item)) => {
+                self.f.take().expect("cannot poll MapErr twice");
+                Ok(Async::Ready(item))
+            }
+            Err(e) => Err(self.f.take().expect("cannot poll MapErr twice")(e)),
+        }
+    }
+}
+
+/// Future for the `inspect` method of `UnionFutureExt`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Inspect<A, F> {
+    future: A,
+    f: Option<F>,
+}
+
+impl<A, F> Inspect<A, F> {
+    fn new(future: A, f: F) -> Inspect<A, F> {
+        Inspect { future, f: Some(f) }
+    }
+}
+
+impl<A, F> Future for Inspect<A, F>
+    where A: Future,
+          F: FnOnce(&A::Item)
+{
+    type Item = A::Item;
+    type Error = A::Error;
+
+    fn poll(&mut self) -> Poll<A::Item, A::Error> {
+        let poll = self.future.poll();
+        if let Ok(Async::Ready(ref item)) = poll {
+            self.f.take().expect("cannot poll Inspect twice")(item);
+        }
+        poll
+    }
+}
+
+/// Future for the `inspect_err` method of `UnionFutureExt`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct InspectErr<A, F> {
+    future: A,
+    f: F,
+}
+
+impl<A, F> InspectErr<A, F> {
+    fn new(future: A, f: F) -> InspectErr<A, F> {
+        InspectErr { future, f }
+    }
+}
+
+impl<A, F> Future for InspectErr<A, F>
+    where A: Future,
+          F: FnMut(&A::Error)
+{
+    type Item = A::Item;
+    type Error = A::Error;
+
+    fn poll(&mut self) -> Poll<A::Item, A::Error> {
+        let poll = self.future.poll();
+        if let Err(ref e) = poll {
+            (self.f)(e);
+        }
+        poll
+    }
+}
+
+// The state of a future that polls the future `A` and then the future `B` created from its
+// result with `C`.
+#[derive(Debug)]
+enum Chain<A, B, C> {
+    First(A, C),
+    Second(B),
+    Done,
+}
+
+impl<A, B, C> Chain<A, B, C>
+    where A: Future,
+          B: Future
+{
+    fn new(a: A, c: C) -> Chain<A, B, C> {
+        Chain::First(a, c)
+    }
+
+    // `f` either resolves the chain with the result of `A` or returns the future `B`
+    fn poll<F>(&mut self, f: F) -> Poll<B::Item, B::Error>
+        where F: FnOnce(Result<A::Item, A::Error>, C) -> Result<Result<B::Item, B::Error>, B>
+    {
+        let result = match *self {
+            Chain::First(ref mut a, _) => {
+                match a.poll() {
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Ok(Async::Ready(t)) => Ok(t),
+                    Err(e) => Err(e),
+                }
+            }
+            Chain::Second(ref mut b) => return b.poll(),
+            Chain::Done => panic!("cannot poll a chained future twice"),
+        };
+        let c = match ::std::mem::replace(self, Chain::Done) {
+            Chain::First(_, c) => c,
+            _ => unreachable!(),
+        };
+        match f(result, c) {
+            Ok(Ok(t)) => Ok(Async::Ready(t)),
+            Ok(Err(e)) => Err(e),
+            Err(mut b) => {
+                let poll = b.poll();
+                *self = Chain::Second(b);
+                poll
+            }
+        }
+    }
+}
+
+/// Future for the `then` method of `UnionFutureExt`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Then<A, B, F>
+    where B: IntoFuture
+{
+    chain: Chain<A, B::Future, F>,
+}
+
+impl<A, B, F> Then<A, B, F>
+    where A: Future,
+          B: IntoFuture
+{
+    fn new(future: A, f: F) -> Then<A, B, F> {
+        Then { chain: Chain::new(future, f) }
+    }
+}
+
+impl<A, B, F> Future for Then<A, B, F>
+    where A: Future,
+          B: IntoFuture,
+          F: FnOnce(Result<A::Item, A::Error>) -> B
+{
+    type Item = B::Item;
+    type Error = B::Error;
+
+    fn poll(&mut self) -> Poll<B::Item, B::Error> {
+        self.chain.poll(|result, f| Err(f(result).into_future()))
+    }
+}
+
+/// Future for the `flatten` method of `UnionFutureExt`.
+#[must_use = "futures do nothing unless polled"]
+pub struct Flatten<A>
+    where A: Future,
+          A::Item: IntoFuture
+{
+    future: A,
+    inner: Option<<A::Item as IntoFuture>::Future>,
+}
+
+impl<A> Flatten<A>
+    where A: Future,
+          A::Item: IntoFuture
+{
+    fn new(future: A) -> Flatten<A> {
+        Flatten { future, inner: None }
+    }
+}
+
+impl<A> fmt::Debug for Flatten<A>
+    where A: Future + fmt::Debug,
+          A::Item: IntoFuture,
+          <A::Item as IntoFuture>::Future: fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Flatten").field("future", &self.future).field("inner", &self.inner).finish()
+    }
+}
+
+impl<A> Future for Flatten<A>
+    where A: Future,
+          A::Item: IntoFuture,
+          <A::Item as IntoFuture>::Error: From<A::Error>
+{
+    type Item = <A::Item as IntoFuture>::Item;
+    type Error = <A::Item as IntoFuture>::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if self.inner.is_none() {
+            match self.future.poll()? {
+                Async::Ready(inner) => self.inner = Some(inner.into_future()),
+                Async::NotReady => return Ok(Async::NotReady),
+            }
+        }
+        match self.inner {
+            Some(ref mut inner) => inner.poll(),
+            None => unreachable!(),
+        }
+    }
+}
+
+/// Future for the `and_then` method of `UnionFutureExt`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct AndThen<A, B, F>
+    where B: IntoFuture
+{
+    chain: Chain<A, B::Future, F>,
+}
+
+impl<A, B, F> AndThen<A, B, F>
+    where A: Future,
+          B: IntoFuture
+{
+    fn new(future: A, f: F) -> AndThen<A, B, F> {
+        AndThen { chain: Chain::new(future, f) }
+    }
+}
+
+impl<A, B, F> Future for AndThen<A, B, F>
+    where A: Future,
+          B: IntoFuture<Error = A::Error>,
+          F: FnOnce(A::Item) -> B
+{
+    type Item = B::Item;
+    type Error = B::Error;
+
+    fn poll(&mut self) -> Poll<B::Item, B::Error> {
+        self.chain.poll(|result, f| {
+            match result {
+                Ok(t) => Err(f(t).into_future()),
+                Err(e) => Ok(Err(e)),
+            }
+        })
+    }
+}
+
+/// Future for the `or_else` method of `UnionFutureExt`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct OrElse<A, B, F>
+    where B: IntoFuture
+{
+    chain: Chain<A, B::Future, F>,
+}
+
+impl<A, B, F> OrElse<A, B, F>
+    where A: Future,
+          B: IntoFuture
+{
+    fn new(future: A, f: F) -> OrElse<A, B, F> {
+        OrElse { chain: Chain::new(future, f) }
+    }
+}
+
+impl<A, B, F> Future for OrElse<A, B, F>
+    where A: Future,
+          B: IntoFuture<Item = A::Item>,
+          F: FnOnce(A::Error) -> B
+{
+    type Item = B::Item;
+    type Error = B::Error;
+
+    fn poll(&mut self) -> Poll<B::Item, B::Error> {
+        self.chain.poll(|result, f| {
+            match result {
+                Ok(t) => Ok(Ok(t)),
+                Err(e) => Err(f(e).into_future()),
+            }
+        })
+    }
+}
+
+// A future which is kept until it resolves and then replaced by its item.
+#[derive(Debug)]
+enum MaybeDone<A>
+    where A: Future
+{
+    NotYet(A),
+    Done(A::Item),
+    Gone,
+}
+
+impl<A> MaybeDone<A>
+    where A: Future
+{
+    // returns whether the future has resolved
+    fn poll(&mut self) -> Result<bool, A::Error> {
+        let item = match *self {
+            MaybeDone::NotYet(ref mut a) => {
+                match a.poll()? {
+                    Async::Ready(item) => item,
+                    Async::NotReady => return Ok(false),
+                }
+            }
+            MaybeDone::Done(_) => return Ok(true),
+            MaybeDone::Gone => panic!("cannot poll Zip twice"),
+        };
+        *self = MaybeDone::Done(item);
+        Ok(true)
+    }
+
+    fn take(&mut self) -> A::Item {
+        match ::std::mem::replace(self, MaybeDone::Gone) {
+            MaybeDone::Done(item) => item,
+            _ => panic!("the future has not resolved"),
+        }
+    }
+}
+
+/// Future for the `zip` method of `UnionFutureExt`.
+#[must_use = "futures do nothing unless polled"]
+pub struct Zip<A, B>
+    where A: Future,
+          B: Future
+{
+    a: MaybeDone<A>,
+    b: MaybeDone<B>,
+}
+
+impl<A, B> Zip<A, B>
+    where A: Future,
+          B: Future
+{
+    fn new(a: A, b: B) -> Zip<A, B> {
+        Zip { a: MaybeDone::NotYet(a), b: MaybeDone::NotYet(b) }
+    }
+}
+
+impl<A, B> fmt::Debug for Zip<A, B>
+    where A: Future + fmt::Debug,
+          A::Item: fmt::Debug,
+          B: Future + fmt::Debug,
+          B::Item: fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Zip").field("a", &self.a).field("b", &self.b).finish()
+    }
+}
+
+impl<A, B> Future for Zip<A, B>
+    where A: Future,
+          B: Future<Error = A::Error>
+{
+    type Item = (A::Item, B::Item);
+    type Error = A::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, A::Error> {
+        // both futures are dropped by an error
+        let done = match self.a.poll().and_then(|a| self.b.poll().map(|b| a & b)) {
+            Ok(done) => done,
+            Err(e) => {
+                self.a = MaybeDone::Gone;
+                self.b = MaybeDone::Gone;
+                return Err(e);
+            }
+        };
+        if done {
+            Ok(Async::Ready((self.a.take(), self.b.take())))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+/// Future for the `race` method of `UnionFutureExt`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Race<A, B> {
+    futures: Option<(A, B)>,
+}
+
+impl<A, B> Race<A, B> {
+    fn new(a: A, b: B) -> Race<A, B> {
+        Race { futures: Some((a, b)) }
+    }
+}
+
+impl<A, B> Future for Race<A, B>
+    where A: Future,
+          B: Future<Item = A::Item, Error = A::Error>
+{
+    type Item = A::Item;
+    type Error = A::Error;
+
+    fn poll(&mut self) -> Poll<A::Item, A::Error> {
+        let poll = match self.futures {
+            Some((ref mut a, ref mut b)) => {
+                match a.poll() {
+                    Ok(Async::NotReady) => b.poll(),
+                    poll => poll,
+                }
+            }
+            None => panic!("cannot poll Race twice"),
+        };
+        if let Ok(Async::NotReady) = poll {
+            return poll;
+        }
+        // the future which lost the race is dropped as soon as the other one resolves
+        self.futures = None;
+        poll
+    }
+}
+
+/// Future for the `ok_into` method of `UnionFutureExt`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct OkInto<A, U> {
+    future: A,
+    item: PhantomData<fn() -> U>,
+}
+
+impl<A, U> OkInto<A, U> {
+    fn new(future: A) -> OkInto<A, U> {
+        OkInto { future, item: PhantomData }
+    }
+}
+
+impl<A, U> Future for OkInto<A, U>
+    where A: Future,
+          U: From<A::Item>
+{
+    type Item = U;
+    type Error = A::Error;
+
+    fn poll(&mut self) -> Poll<U, A::Error> {
+        self.future.poll().map(|poll| poll.map(From::from))
+    }
+}
+
+/// Future for the `err_into` method of `UnionFutureExt`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct ErrInto<A, E> {
+    future: A,
+    error: PhantomData<fn() -> E>,
+}
+
+impl<A, E> ErrInto<A, E> {
+    fn new(future: A) -> ErrInto<A, E> {
+        ErrInto { future, error: PhantomData }
+    }
+}
+
+impl<A, E> Future for ErrInto<A, E>
+    where A: Future,
+          E: From<A::Error>
+{
+    type Item = A::Item;
+    type Error = E;
+
+    fn poll(&mut self) -> Poll<A::Item, E> {
+        self.future.poll().map_err(From::from)
+    }
+}
+
+/// Future for the `from_err` method of `UnionFutureExt`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct FromErr<A, E> {
+    inner: ErrInto<A, E>,
+}
+
+impl<A, E> FromErr<A, E> {
+    fn new(future: A) -> FromErr<A, E> {
+        FromErr { inner: ErrInto::new(future) }
+    }
+}
+
+impl<A, E> Future for FromErr<A, E>
+    where A: Future,
+          E: From<A::Error>
+{
+    type Item = A::Item;
+    type Error = E;
+
+    fn poll(&mut self) -> Poll<A::Item, E> {
+        self.inner.poll()
+    }
+}
+
+/// Future for the `catch_panic` method of `UnionFutureExt`.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct CatchPanic<A> {
+    future: A,
+}
+
+impl<A> CatchPanic<A> {
+    fn new(future: A) -> CatchPanic<A> {
+        CatchPanic { future }
+    }
+}
+
+impl<A> Future for CatchPanic<A>
+    where A: Future,
+          A::Error: From<Box<dyn Any + Send>>
+{
+    type Item = A::Item;
+    type Error = A::Error;
+
+    fn poll(&mut self) -> Poll<A::Item, A::Error> {
+        // the future may be left in an inconsistent state by the panic, polling it again is up
+        // to the caller
+        let future = &mut self.future;
+        match panic::catch_unwind(AssertUnwindSafe(|| future.poll())) {
+            Ok(poll) => poll,
+            Err(payload) => Err(From::from(payload)),
+        }
+    }
+}
+
+/// Future for the `once` method of `UnionFutureExt`.
+///
+/// With debug assertions, polling the future after it has resolved panics with "future polled
+/// after completion", catching a future that is mistakenly polled again. Without debug
+/// assertions, the future only delegates to the inner future.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct OnceFuture<A> {
+    future: A,
+    #[cfg(debug_assertions)]
+    completed: bool,
+}
+
+impl<A> OnceFuture<A> {
+    fn new(future: A) -> OnceFuture<A> {
+        OnceFuture {
+            future,
+            #[cfg(debug_assertions)]
+            completed: false,
+        }
+    }
+}
+
+impl<A> Future for OnceFuture<A>
+    where A: Future
+{
+    type Item = A::Item;
+    type Error = A::Error;
+
+    #[cfg(debug_assertions)]
+    fn poll(&mut self) -> Poll<A::Item, A::Error> {
+        if self.completed {
+            panic!("future polled after completion");
+        }
+        let poll = self.future.poll();
+        self.completed = !matches!(poll, Ok(Async::NotReady));
+        poll
+    }
+
+    #[cfg(not(debug_assertions))]
+    fn poll(&mut self) -> Poll<A::Item, A::Error> {
+        self.future.poll()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{Async, Future};
+    use futures::future::{empty, err, ok, Empty, FutureResult};
+    use super::UnionFutureExt;
+
+    #[derive(PartialEq, Debug)]
+    pub struct Error;
+
+    ::union_future!(TestFut<u64, Error>,
+            Nothing => Empty<u64, Error>,
+            Number => FutureResult<u64, Error>);
+
+    #[test]
+    fn map_ok() {
+        let a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(10u64)), a.map_ok(|n| n * 2).poll());
+        let b: TestFut = empty::<u64, Error>().into();
+        assert_eq!(Ok(Async::NotReady), b.map_ok(|n| n * 2).poll());
+        let c: TestFut = err::<u64, Error>(Error).into();
+        assert_eq!(Err(Error), c.map_ok(|n| n.to_string()).poll());
+    }
+
+    #[test]
+    fn map_err() {
+        let a: TestFut = err::<u64, Error>(Error).into();
+        assert_eq!(Err("failed"), UnionFutureExt::map_err(a, |_| "failed").poll());
+        let b: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), UnionFutureExt::map_err(b, |_| "failed").poll());
+        let c: TestFut = empty::<u64, Error>().into();
+        assert_eq!(Ok(Async::NotReady), UnionFutureExt::map_err(c, |_| "failed").poll());
+    }
+
+    #[test]
+    fn inspect() {
+        let mut seen = Vec::new();
+        let a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), UnionFutureExt::inspect(a, |n| seen.push(*n)).poll());
+        let b: TestFut = empty::<u64, Error>().into();
+        assert_eq!(Ok(Async::NotReady), UnionFutureExt::inspect(b, |n| seen.push(*n)).poll());
+        let c: TestFut = err::<u64, Error>(Error).into();
+        assert_eq!(Err(Error), UnionFutureExt::inspect(c, |n| seen.push(*n)).poll());
+        assert_eq!(vec![5], seen);
+
+        // the closure is only called once, so it may move out of its captures
+        let d: TestFut = ok::<u64, Error>(5).into();
+        let mut d = UnionFutureExt::inspect(d, move |n| drop((seen, n)));
+        assert_eq!(Ok(Async::Ready(5u64)), d.poll());
+    }
+
+    #[test]
+    fn inspect_err() {
+        let mut failures = 0;
+        let a: TestFut = err::<u64, Error>(Error).into();
+        assert_eq!(Err(Error), a.inspect_err(|_| failures += 1).poll());
+        let b: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), b.inspect_err(|_| failures += 1).poll());
+        let c: TestFut = empty::<u64, Error>().into();
+        assert_eq!(Ok(Async::NotReady), c.inspect_err(|_| failures += 1).poll());
+        assert_eq!(1, failures);
+    }
+
+    #[test]
+    fn then() {
+        let a: TestFut = ok::<u64, Error>(5).into();
+        let mut a = UnionFutureExt::then(a, |r| ok::<bool, ()>(r.is_ok()));
+        assert_eq!(Ok(Async::Ready(true)), a.poll());
+        let b: TestFut = err::<u64, Error>(Error).into();
+        let mut b = UnionFutureExt::then(b, |r| ok::<bool, ()>(r.is_ok()));
+        assert_eq!(Ok(Async::Ready(false)), b.poll());
+        let c: TestFut = empty::<u64, Error>().into();
+        let mut c = UnionFutureExt::then(c, |r| ok::<bool, ()>(r.is_ok()));
+        assert_eq!(Ok(Async::NotReady), c.poll());
+        let d: TestFut = ok::<u64, Error>(5).into();
+        let mut d = UnionFutureExt::then(d, |_| empty::<bool, ()>());
+        assert_eq!(Ok(Async::NotReady), d.poll());
+        assert_eq!(Ok(Async::NotReady), d.poll());
+    }
+
+    #[test]
+    fn into_future_closures() {
+        let a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), UnionFutureExt::then(a, |r| r).poll());
+        let b: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(6u64)), UnionFutureExt::and_then(b, |n| Ok(n + 1)).poll());
+        let c: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Err(Error), UnionFutureExt::and_then(c, |_| Err::<u64, _>(Error)).poll());
+        let d: TestFut = err::<u64, Error>(Error).into();
+        assert_eq!(Ok(Async::Ready(0u64)), UnionFutureExt::or_else(d, |_| Ok::<_, ()>(0)).poll());
+        let e: TestFut = empty::<u64, Error>().into();
+        assert_eq!(Ok(Async::NotReady), UnionFutureExt::and_then(e, |n| Ok(n + 1)).poll());
+    }
+
+    #[test]
+    fn flatten() {
+        ::union_future!(NestedFut<FutureResult<u64, Error>, Error>,
+                Nothing => Empty<FutureResult<u64, Error>, Error>,
+                Number => FutureResult<FutureResult<u64, Error>, Error>);
+
+        let a: NestedFut = ok::<_, Error>(ok::<u64, Error>(5)).into();
+        assert_eq!(Ok(Async::Ready(5u64)), UnionFutureExt::flatten(a).poll());
+        let b: NestedFut = ok::<_, Error>(err::<u64, Error>(Error)).into();
+        assert_eq!(Err(Error), UnionFutureExt::flatten(b).poll());
+        let c: NestedFut = err::<FutureResult<u64, Error>, Error>(Error).into();
+        assert_eq!(Err(Error), UnionFutureExt::flatten(c).poll());
+        let d: NestedFut = empty::<FutureResult<u64, Error>, Error>().into();
+        assert_eq!(Ok(Async::NotReady), UnionFutureExt::flatten(d).poll());
+
+        ::union_future!(ResultFut<Result<u64, Error>, Error>,
+                Nothing => Empty<Result<u64, Error>, Error>,
+                Number => FutureResult<Result<u64, Error>, Error>);
+
+        let e: ResultFut = ok::<_, Error>(Ok(5)).into();
+        assert_eq!(Ok(Async::Ready(5u64)), UnionFutureExt::flatten(e).poll());
+        let f: ResultFut = ok::<_, Error>(Err(Error)).into();
+        assert_eq!(Err(Error), UnionFutureExt::flatten(f).poll());
+    }
+
+    #[test]
+    fn and_then() {
+        let a: TestFut = ok::<u64, Error>(5).into();
+        let mut a = UnionFutureExt::and_then(a, |n| ok::<u64, Error>(n + 1));
+        assert_eq!(Ok(Async::Ready(6u64)), a.poll());
+        let b: TestFut = ok::<u64, Error>(5).into();
+        let mut b = UnionFutureExt::and_then(b, |_| err::<u64, Error>(Error));
+        assert_eq!(Err(Error), b.poll());
+        let c: TestFut = err::<u64, Error>(Error).into();
+        let mut c = UnionFutureExt::and_then(c, |n| ok::<u64, Error>(n + 1));
+        assert_eq!(Err(Error), c.poll());
+        let d: TestFut = empty::<u64, Error>().into();
+        let mut d = UnionFutureExt::and_then(d, |n| ok::<u64, Error>(n + 1));
+        assert_eq!(Ok(Async::NotReady), d.poll());
+    }
+
+    #[test]
+    fn or_else() {
+        let a: TestFut = err::<u64, Error>(Error).into();
+        let mut a = UnionFutureExt::or_else(a, |_| ok::<u64, ()>(0));
+        assert_eq!(Ok(Async::Ready(0u64)), a.poll());
+        let b: TestFut = err::<u64, Error>(Error).into();
+        let mut b = UnionFutureExt::or_else(b, |_| err::<u64, ()>(()));
+        assert_eq!(Err(()), b.poll());
+        let c: TestFut = ok::<u64, Error>(5).into();
+        let mut c = UnionFutureExt::or_else(c, |_| ok::<u64, ()>(0));
+        assert_eq!(Ok(Async::Ready(5u64)), c.poll());
+        let d: TestFut = empty::<u64, Error>().into();
+        let mut d = UnionFutureExt::or_else(d, |_| ok::<u64, ()>(0));
+        assert_eq!(Ok(Async::NotReady), d.poll());
+    }
+
+    #[test]
+    fn zip() {
+        let a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready((5u64, "five"))), a.zip(ok::<_, Error>("five")).poll());
+        let b: TestFut = empty::<u64, Error>().into();
+        assert_eq!(Ok(Async::NotReady), b.zip(ok::<_, Error>("five")).poll());
+        let c: TestFut = empty::<u64, Error>().into();
+        assert_eq!(Err(Error), c.zip(err::<&str, Error>(Error)).poll());
+        let d: TestFut = err::<u64, Error>(Error).into();
+        assert_eq!(Err(Error), d.zip(empty::<&str, Error>()).poll());
+    }
+
+    #[test]
+    fn race() {
+        use std::rc::Rc;
+
+        let a: TestFut = empty::<u64, Error>().into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.race(ok::<u64, Error>(5)).poll());
+        let b: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), b.race(err::<u64, Error>(Error)).poll());
+        let c: TestFut = empty::<u64, Error>().into();
+        assert_eq!(Err(Error), c.race(err::<u64, Error>(Error)).poll());
+        let d: TestFut = empty::<u64, Error>().into();
+        assert_eq!(Ok(Async::NotReady), d.race(empty::<u64, Error>()).poll());
+
+        let loser = Rc::new(1u64);
+        let held = loser.clone();
+        let e: TestFut = ok::<u64, Error>(5).into();
+        let mut e = e.race(empty::<u64, Error>().map(move |n| n + *held));
+        assert_eq!(Ok(Async::Ready(5u64)), e.poll());
+        assert_eq!(1, Rc::strong_count(&loser));
+    }
+
+    #[test]
+    fn ok_into() {
+        let a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u128)), a.ok_into::<u128>().poll());
+        let b: TestFut = err::<u64, Error>(Error).into();
+        let mut b = b.ok_into();
+        let poll: Result<Async<Option<u64>>, Error> = b.poll();
+        assert_eq!(Err(Error), poll);
+    }
+
+    #[test]
+    fn err_into() {
+        #[derive(PartialEq, Debug)]
+        struct OtherError;
+
+        impl From<Error> for OtherError {
+            fn from(_: Error) -> OtherError {
+                OtherError
+            }
+        }
+
+        let a: TestFut = err::<u64, Error>(Error).into();
+        assert_eq!(Err(OtherError), a.err_into::<OtherError>().poll());
+        let b: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), b.err_into::<OtherError>().poll());
+    }
+
+    #[test]
+    fn from_err() {
+        let a: TestFut = err::<u64, Error>(Error).into();
+        assert_eq!(Err(Some(Error)), UnionFutureExt::from_err::<Option<Error>>(a).poll());
+        let b: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), UnionFutureExt::from_err::<Option<Error>>(b).poll());
+    }
+
+    #[test]
+    fn catch_panic() {
+        use std::any::Any;
+        use futures::future::{lazy, Lazy};
+
+        #[derive(Debug)]
+        pub struct Panicked(Option<&'static str>);
+
+        impl From<Box<dyn Any + Send>> for Panicked {
+            fn from(payload: Box<dyn Any + Send>) -> Panicked {
+                Panicked(payload.downcast_ref::<&'static str>().cloned())
+            }
+        }
+
+        fn boom() -> FutureResult<u64, Panicked> {
+            panic!("boom")
+        }
+
+        ::union_future!(TestFut<u64, Panicked>,
+                Number => FutureResult<u64, Panicked>,
+                Boom => Lazy<fn() -> FutureResult<u64, Panicked>, FutureResult<u64, Panicked>>);
+
+        let a: TestFut = lazy(boom as fn() -> FutureResult<u64, Panicked>).into();
+        match a.catch_panic().poll() {
+            Err(Panicked(message)) => assert_eq!(Some("boom"), message),
+            poll => panic!("unexpected {:?}", poll),
+        }
+        let b: TestFut = ok::<u64, Panicked>(5).into();
+        assert_eq!(5, b.catch_panic().wait().unwrap());
+    }
+
+    #[test]
+    fn once() {
+        let a: TestFut = empty::<u64, Error>().into();
+        let mut a = a.once();
+        assert_eq!(Ok(Async::NotReady), a.poll());
+        assert_eq!(Ok(Async::NotReady), a.poll());
+        let b: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), b.once().poll());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "future polled after completion")]
+    fn once_polled_after_completion() {
+        let a: TestFut = err::<u64, Error>(Error).into();
+        let mut a = a.once();
+        assert_eq!(Err(Error), a.poll());
+        let _ = a.poll();
+    }
+
+    mod futures_methods {
+        use futures::{future, Async, Future};
+        use super::{Error, TestFut};
+
+        #[test]
+        fn not_shadowed() {
+            // without `UnionFutureExt` in scope, the methods of `futures::Future` are called
+            let a: TestFut = future::ok::<u64, Error>(5).into();
+            let mut a: future::MapErr<TestFut, _> = a.map_err(|_| "failed");
+            assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+            let b: TestFut = future::ok::<u64, Error>(5).into();
+            let mut b: future::AndThen<TestFut, Result<u64, Error>, _> = b.and_then(|n| Ok(n + 1));
+            assert_eq!(Ok(Async::Ready(6u64)), b.poll());
+            let c: TestFut = future::err::<u64, Error>(Error).into();
+            let mut c: future::Then<TestFut, Result<bool, ()>, _> = c.then(|r| Ok(r.is_ok()));
+            assert_eq!(Ok(Async::Ready(false)), c.poll());
+        }
+    }
+}
@@ -0,0 +1,396 @@
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_async_read {
+    ($attrs:tt $vis:tt $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] []
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        $crate::__union_enum!($attrs $vis $name [$($g)*] [$($u)*] [$($w)*]
+                              [$({ [$($cfg)*] $va $n $ft })*]);
+        $crate::__union_pin_drop_guard!($name [$($g)*] [$($u)*] [$($w)*]);
+        $crate::__union_unpin!($name [$($g)*] [$($u)*] [$($w)*] [$({ [$($cfg)*] $va $n $ft })*]);
+
+        #[allow(deprecated, unused_variables)]
+        impl<$($g)*> $crate::__futures_io::AsyncRead for $name<$($u)*> where $($w)* {
+            fn poll_read(self: ::std::pin::Pin<&mut Self>,
+                         cx: &mut ::std::task::Context<'_>,
+                         buf: &mut [u8])
+                -> ::std::task::Poll<::std::io::Result<usize>> {
+                // the variants are never moved out of the pinned enum
+                unsafe {
+                    match *self.get_unchecked_mut() {
+                        $(
+                            $($cfg)* $name::$n(ref mut r) => {
+                                ::std::pin::Pin::new_unchecked(r).poll_read(cx, buf)
+                            }
+                            ),*
+                    }
+                }
+            }
+
+            fn poll_read_vectored(self: ::std::pin::Pin<&mut Self>,
+                                  cx: &mut ::std::task::Context<'_>,
+                                  bufs: &mut [::std::io::IoSliceMut<'_>])
+                -> ::std::task::Poll<::std::io::Result<usize>> {
+                // the variants are never moved out of the pinned enum
+                unsafe {
+                    match *self.get_unchecked_mut() {
+                        $(
+                            $($cfg)* $name::$n(ref mut r) => {
+                                ::std::pin::Pin::new_unchecked(r).poll_read_vectored(cx, bufs)
+                            }
+                            ),*
+                    }
+                }
+            }
+        }
+    );
+}
+
+/// A macro to create an asynchronous reader that has branched from multiple underlying readers
+/// of distinct types.
+///
+/// The enum implements `futures_io::AsyncRead` by delegating to the active variant. The pin is
+/// projected onto the variant, so the underlying readers do not need to be `Unpin`. For that
+/// reason the generated enum must implement neither `Drop` nor `Unpin`, and it is `Unpin` when
+/// all of the underlying readers are.
+///
+/// ```
+/// #[macro_use]
+/// extern crate union_future;
+///
+/// # pub struct AsyncFile;
+/// # impl union_future::__futures_io::AsyncRead for AsyncFile {
+/// #     fn poll_read(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>, _: &mut [u8])
+/// #         -> std::task::Poll<std::io::Result<usize>> {
+/// #         panic!("Unimplemented")
+/// #     }
+/// # }
+/// union_async_read!(DataSource,
+///       File => AsyncFile,
+///       Memory => &'static [u8]);
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate union_future;
+///
+/// # pub struct AsyncFile(std::marker::PhantomPinned);
+/// # impl union_future::__futures_io::AsyncRead for AsyncFile {
+/// #     fn poll_read(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>, _: &mut [u8])
+/// #         -> std::task::Poll<std::io::Result<usize>> {
+/// #         panic!("Unimplemented")
+/// #     }
+/// # }
+/// union_async_read!(DataSource,
+///       File => AsyncFile,
+///       Memory => &'static [u8]);
+///
+/// impl Unpin for DataSource {}
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! union_async_read {
+    ($($args:tt)*) => (
+        $crate::__union_parse!(@start __union_async_read $($args)*);
+    );
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_async_write {
+    ($attrs:tt $vis:tt $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] []
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        $crate::__union_enum!($attrs $vis $name [$($g)*] [$($u)*] [$($w)*]
+                              [$({ [$($cfg)*] $va $n $ft })*]);
+        $crate::__union_pin_drop_guard!($name [$($g)*] [$($u)*] [$($w)*]);
+        $crate::__union_unpin!($name [$($g)*] [$($u)*] [$($w)*] [$({ [$($cfg)*] $va $n $ft })*]);
+
+        #[allow(deprecated, unused_variables)]
+        impl<$($g)*> $crate::__futures_io::AsyncWrite for $name<$($u)*> where $($w)* {
+            fn poll_write(self: ::std::pin::Pin<&mut Self>,
+                          cx: &mut ::std::task::Context<'_>,
+                          buf: &[u8])
+                -> ::std::task::Poll<::std::io::Result<usize>> {
+                // the variants are never moved out of the pinned enum
+                unsafe {
+                    match *self.get_unchecked_mut() {
+                        $(
+                            $($cfg)* $name::$n(ref mut w) => {
+                                ::std::pin::Pin::new_unchecked(w).poll_write(cx, buf)
+                            }
+                            ),*
+                    }
+                }
+            }
+
+            fn poll_write_vectored(self: ::std::pin::Pin<&mut Self>,
+                                   cx: &mut ::std::task::Context<'_>,
+                                   bufs: &[::std::io::IoSlice<'_>])
+                -> ::std::task::Poll<::std::io::Result<usize>> {
+                // the variants are never moved out of the pinned enum
+                unsafe {
+                    match *self.get_unchecked_mut() {
+                        $(
+                            $($cfg)* $name::$n(ref mut w) => {
+                                ::std::pin::Pin::new_unchecked(w).poll_write_vectored(cx, bufs)
+                            }
+                            ),*
+                    }
+                }
+            }
+
+            fn poll_flush(self: ::std::pin::Pin<&mut Self>,
+                          cx: &mut ::std::task::Context<'_>)
+                -> ::std::task::Poll<::std::io::Result<()>> {
+                // the variants are never moved out of the pinned enum
+                unsafe {
+                    match *self.get_unchecked_mut() {
+                        $(
+                            $($cfg)* $name::$n(ref mut w) => {
+                                ::std::pin::Pin::new_unchecked(w).poll_flush(cx)
+                            }
+                            ),*
+                    }
+                }
+            }
+
+            fn poll_close(self: ::std::pin::Pin<&mut Self>,
+                          cx: &mut ::std::task::Context<'_>)
+                -> ::std::task::Poll<::std::io::Result<()>> {
+                // the variants are never moved out of the pinned enum
+                unsafe {
+                    match *self.get_unchecked_mut() {
+                        $(
+                            $($cfg)* $name::$n(ref mut w) => {
+                                ::std::pin::Pin::new_unchecked(w).poll_close(cx)
+                            }
+                            ),*
+                    }
+                }
+            }
+        }
+    );
+}
+
+/// A macro to create an asynchronous writer that has branched from multiple underlying writers
+/// of distinct types.
+///
+/// The enum implements `futures_io::AsyncWrite` by delegating `poll_write`, `poll_flush` and
+/// `poll_close` to the active variant. As with `union_async_read`, the pin is projected onto the
+/// variant and the generated enum must implement neither `Drop` nor `Unpin`.
+///
+/// ```
+/// #[macro_use]
+/// extern crate union_future;
+///
+/// # pub struct TcpStream;
+/// # impl union_future::__futures_io::AsyncWrite for TcpStream {
+/// #     fn poll_write(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>, _: &[u8])
+/// #         -> std::task::Poll<std::io::Result<usize>> {
+/// #         panic!("Unimplemented")
+/// #     }
+/// #     fn poll_flush(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>)
+/// #         -> std::task::Poll<std::io::Result<()>> {
+/// #         panic!("Unimplemented")
+/// #     }
+/// #     fn poll_close(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>)
+/// #         -> std::task::Poll<std::io::Result<()>> {
+/// #         panic!("Unimplemented")
+/// #     }
+/// # }
+/// union_async_write!(DataSink,
+///       Memory => Vec<u8>,
+///       Net => TcpStream);
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate union_future;
+///
+/// # pub struct TcpStream(std::marker::PhantomPinned);
+/// # impl union_future::__futures_io::AsyncWrite for TcpStream {
+/// #     fn poll_write(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>, _: &[u8])
+/// #         -> std::task::Poll<std::io::Result<usize>> {
+/// #         panic!("Unimplemented")
+/// #     }
+/// #     fn poll_flush(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>)
+/// #         -> std::task::Poll<std::io::Result<()>> {
+/// #         panic!("Unimplemented")
+/// #     }
+/// #     fn poll_close(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>)
+/// #         -> std::task::Poll<std::io::Result<()>> {
+/// #         panic!("Unimplemented")
+/// #     }
+/// # }
+/// union_async_write!(DataSink,
+///       Memory => Vec<u8>,
+///       Net => TcpStream);
+///
+/// impl Unpin for DataSink {}
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! union_async_write {
+    ($($args:tt)*) => (
+        $crate::__union_parse!(@start __union_async_write $($args)*);
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use __futures_io::{AsyncRead, AsyncWrite};
+    use std::io;
+    use std::marker::PhantomPinned;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, Waker};
+
+    pub struct PinnedReader {
+        data: &'static [u8],
+        _pin: PhantomPinned,
+    }
+
+    impl AsyncRead for PinnedReader {
+        fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8])
+            -> Poll<io::Result<usize>> {
+            let data = unsafe { &mut self.get_unchecked_mut().data };
+            Pin::new(data).poll_read(cx, buf)
+        }
+    }
+
+    union_async_read!(TestRead,
+            Memory => &'static [u8],
+            Pinned => PinnedReader);
+
+    fn read(r: &mut Pin<Box<TestRead>>) -> io::Result<Vec<u8>> {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut buf = [0u8; 16];
+        match r.as_mut().poll_read(&mut cx, &mut buf) {
+            Poll::Ready(n) => n.map(|n| buf[..n].to_vec()),
+            Poll::Pending => panic!("reader should be ready"),
+        }
+    }
+
+    #[test]
+    fn unpin_reader() {
+        let reader: TestRead = (&b"hello"[..]).into();
+        assert_eq!(b"hello".to_vec(), read(&mut Box::pin(reader)).unwrap());
+    }
+
+    #[test]
+    fn pinned_reader() {
+        let reader: TestRead = PinnedReader { data: b"world", _pin: PhantomPinned }.into();
+        assert_eq!(b"world".to_vec(), read(&mut Box::pin(reader)).unwrap());
+    }
+
+    #[test]
+    fn unpin_reader_not_boxed() {
+        union_async_read!(MemoryRead,
+                Memory => &'static [u8]);
+
+        let mut reader: MemoryRead = (&b"hello"[..]).into();
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut buf = [0u8; 16];
+        match Pin::new(&mut reader).poll_read(&mut cx, &mut buf) {
+            Poll::Ready(Ok(n)) => assert_eq!(b"hello", &buf[..n]),
+            _ => panic!("reader should be ready"),
+        }
+    }
+
+    #[test]
+    fn borrowed_reader() {
+        union_async_read!(BorrowedRead<'a>,
+                Memory => &'a [u8]);
+
+        let data = b"borrowed".to_vec();
+        let mut reader = Box::pin(BorrowedRead::from(&data[..]));
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut buf = [0u8; 16];
+        match reader.as_mut().poll_read(&mut cx, &mut buf) {
+            Poll::Ready(Ok(n)) => assert_eq!(&data[..], &buf[..n]),
+            _ => panic!("reader should be ready"),
+        }
+    }
+
+    pub struct PinnedWriter {
+        written: Vec<u8>,
+        closed: bool,
+        _pin: PhantomPinned,
+    }
+
+    impl AsyncWrite for PinnedWriter {
+        fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8])
+            -> Poll<io::Result<usize>> {
+            let written = unsafe { &mut self.get_unchecked_mut().written };
+            Pin::new(written).poll_write(cx, buf)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            unsafe { self.get_unchecked_mut().closed = true };
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    union_async_write!(TestWrite,
+            Memory => Vec<u8>,
+            Pinned => PinnedWriter);
+
+    fn write_and_close(w: &mut Pin<Box<TestWrite>>, data: &[u8]) {
+        let mut cx = Context::from_waker(Waker::noop());
+        match w.as_mut().poll_write(&mut cx, data) {
+            Poll::Ready(Ok(n)) => assert_eq!(data.len(), n),
+            _ => panic!("writer should be ready"),
+        }
+        match w.as_mut().poll_flush(&mut cx) {
+            Poll::Ready(Ok(())) => {}
+            _ => panic!("writer should flush"),
+        }
+        match w.as_mut().poll_close(&mut cx) {
+            Poll::Ready(Ok(())) => {}
+            _ => panic!("writer should close"),
+        }
+    }
+
+    #[test]
+    fn unpin_writer() {
+        let mut writer = Box::pin(TestWrite::from(Vec::new()));
+        write_and_close(&mut writer, b"hello");
+        match *writer {
+            TestWrite::Memory(ref v) => assert_eq!(b"hello".to_vec(), *v),
+            TestWrite::Pinned(_) => panic!("expected memory writer"),
+        }
+    }
+
+    #[test]
+    fn unpin_writer_not_boxed() {
+        union_async_write!(MemoryWrite,
+                Memory => Vec<u8>);
+
+        let mut writer = MemoryWrite::from(Vec::new());
+        let mut cx = Context::from_waker(Waker::noop());
+        match Pin::new(&mut writer).poll_write(&mut cx, b"hello") {
+            Poll::Ready(Ok(n)) => assert_eq!(5, n),
+            _ => panic!("writer should be ready"),
+        }
+        let MemoryWrite::Memory(v) = writer;
+        assert_eq!(b"hello".to_vec(), v);
+    }
+
+    #[test]
+    fn pinned_writer() {
+        let inner = PinnedWriter { written: Vec::new(), closed: false, _pin: PhantomPinned };
+        let mut writer = Box::pin(TestWrite::from(inner));
+        write_and_close(&mut writer, b"world");
+        match *writer {
+            TestWrite::Pinned(ref w) => {
+                assert_eq!(b"world".to_vec(), w.written);
+                assert!(w.closed);
+            }
+            TestWrite::Memory(_) => panic!("expected pinned writer"),
+        }
+    }
+}
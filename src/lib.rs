@@ -14,6 +14,24 @@
 //! Also, as an added bonus, the macro will derive the `From` trait for the underlying state
 //! machines in order to make the branched code clean.
 //!
+//! The `union_future_03` macro generates the same kind of enum for the `Pin` based futures 0.3
+//! API, implementing `std::future::Future<Output = Result<Item, Error>>`. For futures that do
+//! not resolve to a `Result`, `union_future_std` only relies on the standard library and
+//! converts the `Output` of each underlying future with the `From` trait.
+//!
+//! Streams and sinks that branch can be unified the same way with the `union_stream`,
+//! `union_try_stream` and `union_sink` macros, which implement the futures 0.1 traits and are
+//! only available with the `futures-01` feature. With the `futures-io` feature enabled, the
+//! `union_async_read` and `union_async_write` macros do the same for readers and writers
+//! implementing `futures_io::AsyncRead` and `futures_io::AsyncWrite`.
+//!
+//! The `union_future_impl` macro only generates the impls of `union_future` for an enum which is
+//! defined elsewhere, `union_future_merge` merges union futures into a new one and
+//! `union_future_split` splits one into new ones.
+//!
+//! For tests, the `mock_future` macro creates a future which resolves or fails after a given
+//! number of polls, to drive the variants of a union future through their states.
+//!
 //! ## Installation
 //!
 //! Add this to your `Cargo.toml`:
@@ -23,6 +41,47 @@
 //! union-future = "0.1"
 //! futures = "0.1"
 //! ```
+//!
+//! ## Features
+//!
+//! The `futures-01` feature is enabled by default and makes `union_future` generate an
+//! implementation of the futures 0.1 `Future` trait. Disabling the default features and enabling
+//! `futures-03` instead makes `union_future` behave like `union_future_03`, which eases an
+//! incremental migration to futures 0.3. The two features are mutually exclusive.
+//!
+//! ```toml
+//! [dependencies]
+//! union-future = { version = "0.1", default-features = false, features = ["futures-03"] }
+//! ```
+//!
+//! Alternatively, the `compat` feature adds a `compat` method to the futures 0.1 enums generated
+//! by `union_future`, which wraps it into a `futures::compat::Compat01As03` future that can be
+//! awaited from futures 0.3 code.
+//!
+//! The `futures-core` feature implements the `FusedFuture` trait of futures 0.3 for the enums
+//! generated by `union_future_03` and `union_future_std` whose variants all implement it, which
+//! `futures::select!` and `futures::select_biased!` require. Variants such as
+//! `std::future::Ready` are not fused, in which case the enum is wrapped with `FutureExt::fuse` of
+//! futures 0.3 before it is selected on, like any other future that is not fused. The enums of
+//! `union_future_03` are also a `TryFuture` through the blanket impl of `futures-core` for futures
+//! resolving to a `Result`, as are the futures 0.1 enums wrapped by their `compat` method.
+//!
+//! The `futures-task` feature adds `From` impls converting the enums generated by
+//! `union_future_03` and `union_future_std` into the boxed `FutureObj` and `LocalFutureObj` of
+//! futures 0.3, for executors with object-safe spawning APIs. `FutureObj` requires a `Send` enum.
+//!
+//! The `test-utils` feature adds the `block_on_union` function, which drives a futures 0.1 union
+//! future to completion on the current thread for tests.
+//!
+//! The `nightly` feature adds the `union_future_infallible` macro, whose enum has the never type
+//! `!` as its error type. It requires a nightly compiler and `#![feature(never_type)]` in the
+//! crate invoking the macro.
+//!
+//! The `derive` feature re-exports `#[derive(UnionFuture)]` from the `union-future-derive` crate,
+//! which generates the futures 0.1 `Future` impl of a regular enum declaration instead. The
+//! `union_future_derive::union_future` attribute does the same for an enum annotated with
+//! `#[union_future(Item = u64, Error = MyError)]`.
+//!
 //! ## Examples
 //!
 //! The basic usage of the macro uses the same Item type from different underlying
@@ -122,54 +181,907 @@
 //! # fn main() {}
 //! ```
 
-#[macro_use]
+#![cfg_attr(all(test, feature = "nightly"), feature(never_type))]
+
+#[cfg(all(feature = "futures-01", feature = "futures-03"))]
+compile_error!("the `futures-01` and `futures-03` features are mutually exclusive");
+
+#[cfg(any(test, feature = "futures-01"))]
 extern crate futures;
+#[cfg(any(test, feature = "futures-01"))]
+#[doc(hidden)]
+pub extern crate futures as __futures;
+#[cfg(all(test, feature = "futures-01"))]
+#[macro_use]
+extern crate quickcheck;
+#[cfg(feature = "futures-io")]
+#[doc(hidden)]
+pub extern crate futures_io as __futures_io;
+#[cfg(feature = "futures-core")]
+#[doc(hidden)]
+pub extern crate futures_core as __futures_core;
+#[cfg(feature = "futures-task")]
+#[doc(hidden)]
+pub extern crate futures_task as __futures_task;
+#[cfg(feature = "compat")]
+#[doc(hidden)]
+pub extern crate futures03 as __futures03;
+#[doc(hidden)]
+pub extern crate paste as __paste;
+#[cfg(feature = "derive")]
+extern crate union_future_derive;
+// the impls of the derive name this crate as `::union_future`
+#[cfg(all(test, feature = "derive"))]
+extern crate self as union_future;
 
-/// A macro to create a future that has branched from multiple underlying futures of distinct
-/// types.
+mod parse;
+
+// Converts the item or error `$e` of the variant `$n` with the `From` trait. The conversion goes
+// through a function named after the variant, so that a missing `From` impl is reported as
+// "required by a bound in `Variant`" and points at the variant of the invocation.
+#[doc(hidden)]
 #[macro_export]
-macro_rules! union_future {
-    ($name:ident<$item:ty, $err:ty>, $($n:ident => $ft:ty),*) => (
-        pub enum $name {
-            $( $n($ft) ),*
+macro_rules! __union_from {
+    ($n:ident $e:ident) => ({
+        #[allow(non_snake_case)]
+        #[inline(always)]
+        fn $n<T, U: ::std::convert::From<T>>(t: T) -> U {
+            ::std::convert::From::from(t)
         }
+        $n($e)
+    });
+}
 
-        impl futures::Future for $name {
+// Polls the future `$f` of the variant `$n` through a function named after the variant, so that a
+// variant which isn't a future is reported as "required by a bound in `Variant`" and points at
+// the variant of the invocation.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_poll {
+    ($n:ident $f:ident) => ({
+        #[allow(non_snake_case)]
+        #[inline(always)]
+        #[track_caller]
+        fn $n<F, T, E>(f: &mut F) -> $crate::__futures::Poll<T, E>
+            where F: $crate::__futures::Future<Item = T, Error = E> + ?Sized
+        {
+            f.poll()
+        }
+        $n($f)
+    });
+}
+
+// Converts the error `$e` of the variant `$n` to the error of the enum, with the closure given by
+// the `err:` option of the variant, by matching on the uninhabited error of an `(infallible)`
+// variant or with the `From` trait otherwise.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_err {
+    ([$va:tt [err ($f:expr) $($o:tt)*]] $n:ident $e:ident) => (
+        ($f)($e)
+    );
+    ([$va:tt [infallible $($o:tt)*]] $n:ident $e:ident) => (
+        match $e {}
+    );
+    ([$va:tt [$o:tt $($os:tt)*]] $n:ident $e:ident) => (
+        $crate::__union_err!([$va [$($os)*]] $n $e)
+    );
+    ([$va:tt []] $n:ident $e:ident) => (
+        $crate::__union_from!($n $e)
+    );
+}
+
+// Calls a cold function in the `poll` arm of a variant marked as `#[cold]`, or not marked as
+// `#[hot]` while another variant is, which tells the optimizer that the arm is unlikely.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_cold {
+    ([$va:tt [hot $($o:tt)*]]) => ();
+    ([$va:tt [cold $($o:tt)*]]) => (
+        $crate::__cold();
+    );
+    ([$va:tt [hot_sibling $($o:tt)*]]) => (
+        $crate::__cold();
+    );
+    ([$va:tt [$o:tt $($os:tt)*]]) => (
+        $crate::__union_cold!([$va [$($os)*]]);
+    );
+    ([$va:tt []]) => ();
+}
+
+#[doc(hidden)]
+#[cold]
+#[inline(never)]
+pub fn __cold() {}
+
+// Invokes the `@poll` arm of the callback `$cb`, which emits the impl with the `poll` method, with
+// `#[inline]` when the attributes of the enum include `#[inline]`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_inline {
+    ([# [inline] $($attrs:tt)*] $cb:ident $($args:tt)*) => (
+        $crate::$cb!(@poll [#[inline]] $($args)*);
+    );
+    ([# $attr:tt $($attrs:tt)*] $cb:ident $($args:tt)*) => (
+        $crate::__union_inline!([$($attrs)*] $cb $($args)*);
+    );
+    ([] $cb:ident $($args:tt)*) => (
+        $crate::$cb!(@poll [] $($args)*);
+    );
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_enum {
+    (@def [$($attr:tt)*] [$($vis:tt)*] $name:ident [$($g:tt)*] [$($w:tt)*]
+     [$({ [$($cfg:tt)*] [[$($va:tt)*] $vo:tt] $n:ident $ft:ty })*]) => (
+        $($attr)*
+        $($vis)* enum $name<$($g)*> where $($w)* {
+            $( $($cfg)* $($va)* $n($ft) ),*
+        }
+    );
+    (@from $name:ident $g:tt $u:tt $w:tt
+     { $cfg:tt [$va:tt [no_from $($o:tt)*]] $n:ident $ft:ty }) => ();
+    (@from $name:ident $g:tt $u:tt $w:tt
+     { $cfg:tt [$va:tt [$o:tt $($os:tt)*]] $n:ident $ft:ty }) => (
+        $crate::__union_enum!(@from $name $g $u $w { $cfg [$va [$($os)*]] $n $ft });
+    );
+    (@from $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     { [$($cfg:tt)*] [$va:tt []] $n:ident $ft:ty }) => (
+        $($cfg)*
+        #[allow(deprecated)]
+        impl<$($g)*> ::std::convert::From<$ft> for $name<$($u)*> where $($w)* {
+            fn from(other: $ft) -> Self {
+                $name::$n(other)
+            }
+        }
+    );
+    // the attributes which are options of the macro rather than attributes of the enum, the
+    // standard traits requested by `#[union_future(impl(...))]` are collected separately
+    (@options $a:tt [$($o:tt)*] $i:tt [# [assert_send] $($attrs:tt)*] $($rest:tt)*) => (
+        $crate::__union_enum!(@options $a [$($o)* assert_send] $i [$($attrs)*] $($rest)*);
+    );
+    (@options $a:tt $o:tt $i:tt [# [inline] $($attrs:tt)*] $($rest:tt)*) => (
+        $crate::__union_enum!(@options $a $o $i [$($attrs)*] $($rest)*);
+    );
+    (@options $a:tt [$($o:tt)*] $i:tt [# [assert_sync] $($attrs:tt)*] $($rest:tt)*) => (
+        $crate::__union_enum!(@options $a [$($o)* assert_sync] $i [$($attrs)*] $($rest)*);
+    );
+    (@options $a:tt $o:tt [$($i:tt)*]
+     [# [union_future(impl($($t:ident),* $(,)*))] $($attrs:tt)*] $($rest:tt)*) => (
+        $crate::__union_enum!(@options $a $o [$($i)* $($t)*] [$($attrs)*] $($rest)*);
+    );
+    (@options [$($a:tt)*] $o:tt $i:tt [# $attr:tt $($attrs:tt)*] $($rest:tt)*) => (
+        $crate::__union_enum!(@options [$($a)* # $attr] $o $i [$($attrs)*] $($rest)*);
+    );
+    (@options $attrs:tt [$($o:ident)*] $i:tt [] $vis:tt $name:ident $g:tt $u:tt $w:tt
+     [$($v:tt)*]) => (
+        $crate::__union_enum!(@def $attrs $vis $name $g $w [$($v)*]);
+        $( $crate::__union_enum!(@from $name $g $u $w $v); )*
+        $crate::__union_methods!($name $g $u $w [$($v)*]);
+        $crate::__union_traits!($i $name $g $u $w [$($v)*]);
+        $( $crate::__union_assert!($o $name $g $u $w); )*
+    );
+    ($attrs:tt $($rest:tt)*) => (
+        $crate::__union_enum!(@options [] [] [] $attrs $($rest)*);
+    );
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_const_count {
+    ($name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] $c:ident) => (
+        #[allow(dead_code, deprecated)]
+        impl<$($g)*> $name<$($u)*> where $($w)* {
+            /// The number of variants, evaluating this fails to compile unless the const
+            /// parameter is equal to it.
+            pub const COUNT: usize = {
+                assert!($c == Self::VARIANT_COUNT,
+                        "the const parameter must be equal to the number of variants");
+                $c
+            };
+        }
+    );
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_pin_drop_guard {
+    ($name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]) => (
+        // projecting the pin onto the variants is only sound if the enum is never moved out of
+        // in a `Drop` impl, so implementing `Drop` becomes a conflicting impl error
+        const _: () = {
+            #[allow(dead_code)]
+            trait MustNotImplDrop {}
+            #[allow(drop_bounds)]
+            impl<T: ::std::ops::Drop> MustNotImplDrop for T {}
+            #[allow(deprecated)]
+            impl<$($g)*> MustNotImplDrop for $name<$($u)*> where $($w)* {}
+        };
+    );
+}
+
+// The `Unpin` impl of an enum whose pin is projected onto the variants. The enum is only `Unpin`
+// when all of its variants are, and implementing `Unpin` for it becomes a conflicting impl error,
+// since an enum which is `Unpin` regardless of its variants could move a pinned variant.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_unpin {
+    ($name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        const _: () = {
+            $crate::__union_traits!(@variants $name [$($g)*] [$($u)*] [$($w)*]
+                                    [$({ [$($cfg)*] $va $n $ft })*]);
+
+            #[allow(deprecated)]
+            impl<$($g)*> ::std::marker::Unpin for $name<$($u)*>
+                where $($w)*
+                      $( for<'__union> <Self as __UnionVariants>::$n: ::std::marker::Unpin, )*
+            {
+            }
+        };
+    );
+}
+
+// The conversions between an enum with two variants and `futures::future::Either`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_either {
+    ($name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     [{ [$($c0:tt)*] $va0:tt $n0:ident $ft0:ty } { [$($c1:tt)*] $va1:tt $n1:ident $ft1:ty }]) => (
+        $($c0)* $($c1)*
+        #[allow(deprecated)]
+        impl<$($g)*> ::std::convert::From<$crate::__futures::future::Either<$ft0, $ft1>>
+            for $name<$($u)*>
+            where $($w)*
+        {
+            fn from(other: $crate::__futures::future::Either<$ft0, $ft1>) -> Self {
+                match other {
+                    $crate::__futures::future::Either::A(f) => $name::$n0(f),
+                    $crate::__futures::future::Either::B(f) => $name::$n1(f),
+                }
+            }
+        }
+
+        $($c0)* $($c1)*
+        #[allow(deprecated)]
+        impl<$($g)*> ::std::convert::From<$name<$($u)*>>
+            for $crate::__futures::future::Either<$ft0, $ft1>
+            where $($w)*
+        {
+            fn from(other: $name<$($u)*>) -> Self {
+                match other {
+                    $name::$n0(f) => $crate::__futures::future::Either::A(f),
+                    $name::$n1(f) => $crate::__futures::future::Either::B(f),
+                }
+            }
+        }
+    );
+    ($name:ident $g:tt $u:tt $w:tt $v:tt) => ();
+}
+
+#[cfg(feature = "compat")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_compat {
+    ($name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]) => (
+        #[allow(dead_code, deprecated)]
+        impl<$($g)*> $name<$($u)*> where $($w)* {
+            /// Converts this future into a futures 0.3 future.
+            pub fn compat(self) -> $crate::__futures03::compat::Compat01As03<Self> {
+                $crate::__futures03::compat::Compat01As03::new(self)
+            }
+        }
+    );
+}
+
+#[cfg(not(feature = "compat"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_compat {
+    ($name:ident $g:tt $u:tt $w:tt) => ();
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_future_01 {
+    (@poll [$($inline:tt)*] $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] [$item:ty, $err:ty]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        #[allow(deprecated)]
+        impl<$($g)*> $crate::__futures::Future for $name<$($u)*> where $($w)* {
             type Item = $item;
             type Error = $err;
 
-            fn poll(&mut self) -> futures::Poll<Self::Item, Self::Error> {
+            #[track_caller]
+            $($inline)*
+            fn poll(&mut self) -> $crate::__futures::Poll<Self::Item, Self::Error> {
                 match *self {
                     $(
-                        $name::$n(ref mut f) => {
-                            match f.poll() {
-                                Ok(futures::Async::Ready(t)) => Ok(futures::Async::Ready(From::from(t))),
-                                Ok(futures::Async::NotReady) => Ok(futures::Async::NotReady),
-                                Err(e) => Err(From::from(e)),
+                        $($cfg)* $name::$n(ref mut f) => {
+                            $crate::__union_cold!($va);
+                            match $crate::__union_poll!($n f) {
+                                ::std::result::Result::Ok($crate::__futures::Async::Ready(t)) => {
+                                    ::std::result::Result::Ok($crate::__futures::Async::Ready(
+                                        $crate::__union_from!($n t)))
+                                }
+                                ::std::result::Result::Ok($crate::__futures::Async::NotReady) => {
+                                    ::std::result::Result::Ok($crate::__futures::Async::NotReady)
+                                }
+                                // unreachable when the error is `Infallible`
+                                #[allow(unreachable_code)]
+                                ::std::result::Result::Err(e) => {
+                                    ::std::result::Result::Err($crate::__union_err!($va $n e))
+                                }
                             }
                         }
                         ),*
                 }
             }
         }
+    );
+    ([$($attrs:tt)*] $vis:tt $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] [$item:ty, $err:ty]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        $crate::__union_enum!([#[must_use = "futures do nothing unless polled"] $($attrs)*] $vis
+                              $name [$($g)*] [$($u)*] [$($w)*]
+                              [$({ [$($cfg)*] $va $n $ft })*]);
+        $crate::__union_compat!($name [$($g)*] [$($u)*] [$($w)*]);
+        $crate::__union_either!($name [$($g)*] [$($u)*] [$($w)*] [$({ [$($cfg)*] $va $n $ft })*]);
+        $crate::__union_combinators!($name [$($g)*] [$($u)*] [$($w)*]);
+        $crate::__union_inline!([$($attrs)*] __union_future_01
+                                $name [$($g)*] [$($u)*] [$($w)*] [$item, $err]
+                                [$({ [$($cfg)*] $va $n $ft })*]);
+    );
+}
+
+// Only the `Future` and `From` impls of `union_future_impl`, for an enum defined elsewhere.
+#[cfg(feature = "futures-01")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_future_impl {
+    ($attrs:tt $vis:tt $name:ident $g:tt $u:tt $w:tt $hdr:tt [$($v:tt)*]) => (
+        $( $crate::__union_enum!(@from $name $g $u $w $v); )*
+        $crate::__union_inline!($attrs __union_future_01 $name $g $u $w $hdr [$($v)*]);
+    );
+}
 
-        $(
-            impl From<$ft> for $name {
-                fn from(other: $ft) -> $name {
-                    $name::$n(other)
+#[cfg(all(feature = "futures-03", not(feature = "futures-01")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_future_impl {
+    ($attrs:tt $vis:tt $name:ident $g:tt $u:tt $w:tt $hdr:tt [$($v:tt)*]) => (
+        $( $crate::__union_enum!(@from $name $g $u $w $v); )*
+        $crate::__union_pin_drop_guard!($name $g $u $w);
+        $crate::__union_unpin!($name $g $u $w [$($v)*]);
+        $crate::__union_inline!($attrs __union_future_03 $name $g $u $w $hdr [$($v)*]);
+    );
+}
+
+/// A macro to create a future that has branched from multiple underlying futures of distinct
+/// types.
+///
+/// The generated enum is `pub` unless a visibility modifier is given. Any visibility accepted
+/// by Rust works, such as `union_future!(pub(crate) QueryFuture<u64, DbError>, ...)`,
+/// `pub(super)`, `pub(in some::path)` or `pub(self)` for a private enum.
+///
+/// A `where` clause following the item and error types, or following the last variant, makes the
+/// enum generic. Every predicate with a plain identifier on the left-hand side declares a type
+/// parameter, so `union_future!(QueryFuture<T, DbError> where T: Clone, ...)` generates
+/// `enum QueryFuture<T> where T: Clone`. The type parameters are declared in the order of their
+/// predicates, and the predicates are emitted on the enum and on all of the generated impls. The
+/// last variant, or the trailing `where` clause, may be followed by a trailing comma.
+///
+/// A variant may box a trait object, such as
+/// `Boxed => Box<dyn Future<Item = u64, Error = DbError>>`, as an escape hatch for the rare future
+/// whose type isn't known statically. Its arm of `poll` polls the trait object dynamically while
+/// the other variants are polled statically.
+///
+/// `poll` matches on the active variant and polls only its future, so the order in which the
+/// variants are declared has no effect on polling, and reordering them does not change the
+/// behavior of the enum. Only `variant_index` and a `#[repr]` discriminant depend on that order.
+///
+/// An enum with a single variant names a future like a newtype does, and can gain more variants
+/// later without changing the code constructing it with `into()`.
+/// An enum with exactly two variants converts from and into the `futures::future::Either` of their
+/// futures with `From` impls, so it is interchangeable with `Either` through `into()`.
+///
+/// An enum without variants, such as `union_future!(Never<u64, DbError>)`, is uninhabited and
+/// stands for a future which is never constructed, like a branch of generic code never taken.
+/// Lifetime parameters are listed before the item and error types, such as
+/// `union_future!(BorrowFuture<'a, &'a str, DbError>, ...)`.
+///
+/// The never type stands for `std::convert::Infallible` in the item and error types, so
+/// `union_future!(Computation<u64, !>, ...)` is a future which never fails. Its variants have
+/// `Infallible` errors, which are converted by the `From` impl of every type for itself.
+///
+/// Attributes preceding the visibility, such as `#[derive(Debug, Clone)]` or doc comments, are
+/// forwarded to the generated enum. They are emitted verbatim, so
+/// `#[cfg_attr(feature = "serde", derive(Serialize))]` is evaluated by the compiler as usual. The
+/// enum is always `#[must_use]`, like the futures of the `futures` crate. A `#[non_exhaustive]`
+/// enum can gain variants without breaking downstream crates, which then have to match it with a
+/// wildcard arm.
+///
+/// A `#[repr(u8)]` enum stores its variant as a leading `u8` discriminant, numbered from zero in
+/// the order of the variants. This limits the enum to 256 variants and may make it larger, since
+/// the discriminant can no longer be stored in a niche of the variants.
+///
+/// A `#[deprecated = "use NewFuture instead"]` enum warns where it is used, but not in the code
+/// generated for it.
+///
+/// The generated `From` impls construct every variant and `poll` reads every variant, so even a
+/// private enum whose variants are never constructed by hand does not trigger `dead_code`
+/// warnings. Lints of the enum can still be configured by forwarding attributes such as
+/// `#[allow(dead_code)]`.
+///
+/// Variants may be preceded by attributes as well. A `#[cfg(...)]` attribute also gates the code
+/// generated for the variant, such as its `From` impl and its arm of `poll`. A `#[deprecated]`
+/// variant warns where it is constructed or matched by name. Rust ignores deprecation of trait
+/// impls, so converting into the variant with `From` does not warn.
+///
+/// The enum has a `variant_name(&self) -> &'static str` method, which returns the name of the
+/// active variant exactly as written in the macro invocation, and a `variant_index(&self) ->
+/// usize` method, which returns the index of the active variant counting from zero. The number of
+/// variants is the associated `VARIANT_COUNT` constant, so `[AtomicU64; Foo::VARIANT_COUNT]` has
+/// an element per variant.
+///
+/// The size of the enum in bytes is the associated `SIZE` constant. It is the size of the largest
+/// variant plus the discriminant, rounded up to the alignment of the enum, so a single large
+/// variant makes every value of the enum large. The discriminant takes no space when the compiler
+/// stores it in a niche of a variant, such as the null pointer of a `Box`. See also
+/// `assert_union_future_size`.
+///
+/// Every variant gets methods named after the variant in snake case, such as `is_db` for `Db` or
+/// `is_io_uring` for `IoUring`:
+///
+/// * `is_<variant>(&self) -> bool` tells whether the variant is active.
+/// * `into_<variant>(self) -> Option<F>` returns the inner future of the variant.
+/// * `as_<variant>(&self) -> Option<&F>` borrows the inner future of the variant.
+/// * `as_mut_<variant>(&mut self) -> Option<&mut F>` mutably borrows the inner future of the
+///   variant.
+///
+/// A `#[union_future(impl(Debug, Clone))]` attribute is not forwarded but implements the listed
+/// traits for the enum, bounded by the variants implementing them. The traits are `Clone`,
+/// `Copy`, `Debug`, `PartialEq`, `Eq`, `Hash`, `PartialOrd`, `Ord` and `Display`, which formats
+/// the active variant without its name. The ordering compares the index of the variants before
+/// the inner values. The traits which are not listed can be derived or implemented by hand as
+/// usual.
+///
+/// An `#[assert_send]` attribute is not forwarded but asserts that the enum is `Send`, which
+/// turns a variant which is not `Send` into an error at the macro invocation rather than where
+/// the future is spawned. The enum is `Send` when all of its variants are `Send`, so the
+/// assertion of a generic enum needs `Send` bounds in its `where` clause. Likewise, an
+/// `#[assert_sync]` attribute asserts that the enum is `Sync`.
+///
+/// An `#[inline]` attribute is not forwarded but marks the generated `poll` as `#[inline]`, so
+/// that a public union future can be inlined into the crates polling it like one which is
+/// generic. Within the crate invoking the macro the optimizer is free to inline `poll` either
+/// way. Trivial variants such as `FutureResult`, or `std::future::Ready` with `union_future_03`,
+/// need no special casing, since polling them only takes their result out of an `Option`.
+///
+/// A `#[no_from]` attribute of a variant is not forwarded but skips its `From` impl, which allows
+/// variants of the same type, such as `Fast => DbFuture<T>, #[no_from] Slow => DbFuture<T>`.
+/// These variants are constructed directly, like `QueryFuture::Slow(future)`.
+///
+/// An `#[assert_unwind_safe]` attribute of a variant is not forwarded but asserts that its type is
+/// `UnwindSafe` and `RefUnwindSafe`, like `AssertUnwindSafe` does, so that a variant holding an
+/// `Rc<RefCell<T>>` doesn't keep the enum from being passed to `catch_unwind`. The other variants
+/// still have to be unwind safe for the enum to be.
+///
+/// A `#[cold]` attribute of a variant is not forwarded but hints to the optimizer that the variant
+/// is rarely polled, by calling a `#[cold]` function in its arm of the generated `poll`. Likewise,
+/// a `#[hot]` attribute marks the common case, which makes every variant that isn't `#[hot]` cold.
+///
+/// The errors of a variant are converted with the `From` trait unless the variant is followed by
+/// an `err:` closure, such as `Remote => HttpFuture, err: |e: HttpError| DbError::Http(e)`, which
+/// avoids a `From` impl that the orphan rules forbid when both error types are foreign. The
+/// closure cannot be followed by the trailing `where` clause. A variant marked as
+/// `(infallible)`, such as `Cached(infallible) => FutureResult<u64, Infallible>`, has an empty
+/// error type like `Infallible`, so its errors are never converted and no
+/// `From<Infallible>` impl is required for the error of the enum.
+///
+/// When the type of a variant isn't a future, or its item or error cannot be converted, the
+/// compiler reports the missing `Future` or `From` impl as "required by a bound in
+/// `<TestFut as futures::Future>::poll::Variant`" and points at the name of the variant in the
+/// invocation.
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate union_future;
+/// # extern crate futures;
+/// # use futures::future::*;
+/// pub struct Bar;
+///
+/// union_future!(TestFut<u64, ()>,
+///       Immediate => FutureResult<u64, ()>,
+///       // error: the trait `From<Bar>` is not implemented for `u64`
+///       Other => FutureResult<Bar, ()>);
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail
+/// # #[macro_use] extern crate union_future;
+/// # extern crate futures;
+/// # use futures::future::*;
+/// union_future!(TestFut<u64, ()>,
+///       Immediate => FutureResult<u64, ()>,
+///       // error: the trait `futures::Future` is not implemented for `String`
+///       Other => String);
+/// # fn main() {}
+/// ```
+///
+/// The generated `poll` is `#[track_caller]`, so a panic of an underlying future whose `poll` is
+/// `#[track_caller]` as well reports the location where the enum is polled rather than a location
+/// inside the macro.
+///
+/// The enum also implements the `UnionFuture` marker trait, which gives it the combinators of
+/// `UnionFutureExt` returning the futures of this crate, which keep the concrete type of the enum
+/// without boxing. They are only available when `UnionFutureExt` is imported, so the methods of
+/// `futures::Future` with the same name keep working as usual:
+///
+/// * `map_ok(f)` maps the item with `f`.
+/// * `map_err(f)` maps the error with `f`.
+/// * `inspect(f)` calls `f` with a reference to the item.
+/// * `inspect_err(f)` calls `f` with a reference to the error.
+/// * `then(f)` continues with the future returned by `f` for the result.
+/// * `flatten()` continues with the future that the item is.
+/// * `and_then(f)` continues with the future returned by `f` for the item.
+/// * `or_else(f)` continues with the future returned by `f` for the error.
+/// * `zip(other)` polls `other` concurrently and resolves to both items.
+/// * `race(other)` polls `other` concurrently and resolves to the first result.
+/// * `ok_into()` converts the item with the `From` trait.
+/// * `err_into()` and `from_err()` convert the error with the `From` trait.
+/// * `catch_panic()` converts a panic of `poll` into the error, which must implement
+///   `From<Box<dyn Any + Send>>`.
+/// * `once()` panics when the future is polled after it has resolved, in builds with debug
+///   assertions.
+///
+/// A `const N: usize` parameter may follow the lifetime parameters, in which case the enum also
+/// gets an associated `COUNT` constant. Evaluating `COUNT`, such as in `[u8; Foo::<3>::COUNT]`,
+/// fails to compile unless `N` is equal to the number of variants.
+#[cfg(feature = "futures-01")]
+#[macro_export]
+macro_rules! union_future {
+    ($($args:tt)*) => (
+        $crate::__union_parse!(@start __union_future_01 $($args)*);
+    );
+}
+
+#[cfg(feature = "nightly")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_future_infallible {
+    ($attrs:tt $vis:tt $name:ident $g:tt $u:tt $w:tt [$item:ty]
+     [$({ $cfg:tt [$va:tt [$($o:tt)*]] $n:ident $ft:ty })*]) => (
+        $crate::__union_future_01!($attrs $vis $name $g $u $w [$item, !]
+                                   [$({ $cfg [$va [infallible $($o)*]] $n $ft })*]);
+    );
+}
+
+/// A macro to create a future that never fails from multiple underlying futures of distinct
+/// types.
+///
+/// The syntax is the same as `union_future`, except that only the item type follows the name.
+/// The error type of the enum is the never type `!`, as is the error type of every variant, so
+/// the generated `poll` never converts an error. This requires the `nightly` feature and
+/// `#![feature(never_type)]` in the crate invoking the macro.
+///
+/// ```ignore
+/// #![feature(never_type)]
+///
+/// union_future_infallible!(pub Lookup<u64>,
+///       Cached => FutureResult<u64, !>,
+///       Computed => Map<FutureResult<u32, !>, fn(u32) -> u64>);
+/// ```
+#[cfg(feature = "nightly")]
+#[macro_export]
+macro_rules! union_future_infallible {
+    ($($args:tt)*) => (
+        $crate::__union_parse!(@start __union_future_infallible $($args)*);
+    );
+}
+
+/// A macro to create a future that has branched from multiple underlying futures of distinct
+/// types.
+///
+/// With the `futures-03` feature, this is the same as `union_future_03`.
+#[cfg(all(feature = "futures-03", not(feature = "futures-01")))]
+#[macro_export]
+macro_rules! union_future {
+    ($($args:tt)*) => (
+        $crate::union_future_03!($($args)*);
+    );
+}
+
+/// Generates the `Future` and `From` impls of `union_future` for an enum which is defined
+/// elsewhere, such as an enum with its own derives or documentation.
+///
+/// The input is the same as for `union_future`, and the enum must have a tuple variant with a
+/// single field for each of the variants given to the macro. Type parameters of the enum are
+/// declared by the `where` clause, the `#[no_from]` and `#[cfg(...)]` attributes of a variant and
+/// the `#[inline]` attribute of the enum apply as usual, while the other attributes and the
+/// visibility are ignored.
+///
+/// ```
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// use futures::future::*;
+///
+/// # #[derive(Debug)]
+/// # pub struct DbError;
+/// #[derive(Debug)]
+/// pub enum QueryFuture {
+///     Cached(FutureResult<u64, DbError>),
+///     Db(Empty<u64, DbError>),
+/// }
+///
+/// union_future_impl!(QueryFuture<u64, DbError>,
+///       Cached => FutureResult<u64, DbError>,
+///       Db => Empty<u64, DbError>);
+///
+/// fn cached(val: u64) -> QueryFuture {
+///     ok(val).into()
+/// }
+/// # fn main() {}
+/// ```
+///
+/// With the `futures-03` feature, the impls are the ones of `union_future_03` instead.
+#[cfg(any(feature = "futures-01", feature = "futures-03"))]
+#[macro_export]
+macro_rules! union_future_impl {
+    ($($args:tt)*) => (
+        $crate::__union_parse!(@start __union_future_impl $($args)*);
+    );
+}
+
+/// A macro to merge union futures, or any other futures, into a new union future.
+///
+/// `union_future_merge!(pub RwFuture<u64, DbError> = ReadFuture + WriteFuture)` is the same as
+/// `union_future!(pub RwFuture<u64, DbError>, ReadFuture => ReadFuture, WriteFuture =>
+/// WriteFuture)`, so each merged future is a variant named after its type, which keeps the
+/// variants of the merged union futures nested rather than flattening them. The `From` impls convert each of the merged
+/// futures into the new one, and their items and errors are converted with the `From` trait as
+/// usual. The merged types must be identifiers, such as the names of non-generic union futures.
+///
+/// ```
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// use futures::future::*;
+///
+/// # pub struct DbError;
+/// union_future!(ReadFuture<u64, DbError>,
+///       Cached => FutureResult<u64, DbError>,
+///       Db => Empty<u64, DbError>);
+/// union_future!(WriteFuture<(), DbError>,
+///       Db => Empty<(), DbError>);
+///
+/// # impl From<()> for Op { fn from(_: ()) -> Op { Op::Written } }
+/// # impl From<u64> for Op { fn from(v: u64) -> Op { Op::Read(v) } }
+/// pub enum Op {
+///     Read(u64),
+///     Written,
+/// }
+///
+/// union_future_merge!(RwFuture<Op, DbError> = ReadFuture + WriteFuture);
+///
+/// fn read(cached: u64) -> RwFuture {
+///     ReadFuture::from(ok(cached)).into()
+/// }
+/// # fn main() {}
+/// ```
+#[cfg(any(feature = "futures-01", feature = "futures-03"))]
+#[macro_export]
+macro_rules! union_future_merge {
+    ($($args:tt)*) => (
+        $crate::__union_merge!([] $($args)*);
+    );
+}
+
+// Collects the tokens preceding the `=` of `union_future_merge`, which are the attributes,
+// visibility, name and header of the new union future.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_merge {
+    ([$($head:tt)*] = $first:ident $(+ $t:ident)*) => (
+        $crate::union_future!($($head)*, $first => $first $(, $t => $t)*);
+    );
+    ([$($head:tt)*] $next:tt $($rest:tt)*) => (
+        $crate::__union_merge!([$($head)* $next] $($rest)*);
+    );
+}
+
+/// A macro to split a union future into new union futures with subsets of its variants.
+///
+/// The split union future is given with its item and error types. Each subset gives the name of a
+/// new union future followed by `contains` and its variants separated by `|`, each with the same
+/// name and type as in the split union future, since a macro cannot look up the variants of an
+/// existing enum. The new union futures have the same item and error types, and a `TryFrom` impl
+/// which converts the split union future when its variant is one of their own, giving it back
+/// otherwise. The split union future is declared like the enum of `union_future`, and its
+/// attributes and visibility apply to the new union futures.
+///
+/// ```
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// use std::convert::TryFrom;
+/// use futures::future::*;
+///
+/// # pub struct DbError;
+/// union_future!(QueryFuture<u64, DbError>,
+///       Cached => FutureResult<u64, DbError>,
+///       Db => Empty<u64, DbError>);
+///
+/// union_future_split!(QueryFuture<u64, DbError> =>
+///       (FastFuture contains Cached => FutureResult<u64, DbError>),
+///       (SlowFuture contains Db => Empty<u64, DbError>));
+///
+/// fn fast(query: QueryFuture) -> Option<FastFuture> {
+///     FastFuture::try_from(query).ok()
+/// }
+/// # fn main() {}
+/// ```
+///
+/// The new union futures cannot be generic, and their variants cannot have attributes, so such
+/// input is rejected:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// use futures::future::*;
+///
+/// union_future!(QueryFuture<T, ()> where T: Clone,
+///       Cached => FutureResult<T, ()>,
+///       Db => Empty<T, ()>);
+///
+/// union_future_split!(QueryFuture<T, ()> where T: Clone =>
+///       (FastFuture contains Cached => FutureResult<T, ()>),
+///       (SlowFuture contains Db => Empty<T, ()>));
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// use futures::future::*;
+///
+/// union_future!(QueryFuture<u64, ()>,
+///       Cached => FutureResult<u64, ()>,
+///       Db => Empty<u64, ()>);
+///
+/// union_future_split!(QueryFuture<u64, ()> =>
+///       (FastFuture contains #[cfg(test)] Cached => FutureResult<u64, ()>),
+///       (SlowFuture contains Db => Empty<u64, ()>));
+/// # fn main() {}
+/// ```
+#[cfg(any(feature = "futures-01", feature = "futures-03"))]
+#[macro_export]
+macro_rules! union_future_split {
+    ($($args:tt)*) => (
+        $crate::__union_split!(@head [] $($args)*);
+    );
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_split {
+    // the split union future preceding the `=>`, which is parsed like the input of the other
+    // macros with the subsets as its body
+    (@head [$($h:tt)*] => $($subsets:tt)*) => (
+        $crate::__union_parse!(@start __union_split $($h)* { $($subsets)* });
+    );
+    (@head [$($h:tt)*] $t:tt $($rest:tt)*) => (
+        $crate::__union_split!(@head [$($h)* $t] $($rest)*);
+    );
+    (@subset [$($attrs:tt)*] [$($vis:tt)*] $name:ident [$($h:ty),*]
+     ($sub:ident contains $($n:ident => $ft:ty)|+)) => (
+        $crate::union_future!($($attrs)* $($vis)* $sub<$($h),*>, $($n => $ft),+);
+
+        #[allow(deprecated)]
+        impl ::std::convert::TryFrom<$name> for $sub {
+            type Error = $name;
+
+            fn try_from(other: $name) -> ::std::result::Result<$sub, $name> {
+                match other {
+                    $( $name::$n(f) => ::std::result::Result::Ok($sub::$n(f)), )+
+                    // unreachable when the subset has all of the variants
+                    #[allow(unreachable_patterns)]
+                    other => ::std::result::Result::Err(other),
                 }
-            })*
+            }
+        }
+    );
+    (@subset $attrs:tt $vis:tt $name:ident $hdr:tt ($sub:ident $($rest:tt)*)) => (
+        compile_error!(concat!("the variants of `", stringify!($sub), "` must be given as `Variant \
+                                => Type` separated by `|`, union_future_split! does not support \
+                                attributes of the variants"));
+    );
+    ($attrs:tt $vis:tt $name:ident [] [] [] $hdr:tt { $($subset:tt),+ $(,)* }) => (
+        $( $crate::__union_split!(@subset $attrs $vis $name $hdr $subset); )+
+    );
+    ($attrs:tt $vis:tt $name:ident $g:tt $u:tt $w:tt $hdr:tt $subsets:tt) => (
+        compile_error!(concat!("union_future_split! does not support generics, `",
+                               stringify!($name), "` must only have item and error types"));
     );
 }
 
-#[cfg(test)]
+/// Asserts at compile time that a type, such as an enum generated by `union_future`, is at most
+/// `max_bytes` bytes large.
+///
+/// This keeps a new large variant from silently inflating the size of the enum, which is the
+/// size of every future holding it. The assertion may be used at module level or in a function.
+///
+/// ```
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// use futures::future::*;
+///
+/// union_future!(NumberFuture<u64, ()>,
+///       Cached => FutureResult<u64, ()>,
+///       Nothing => Empty<u64, ()>);
+///
+/// assert_union_future_size!(NumberFuture, max_bytes: 32);
+/// # fn main() {}
+/// ```
+///
+/// A type larger than `max_bytes` fails to compile:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate union_future;
+///
+/// assert_union_future_size!([u8; 256], max_bytes: 128);
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! assert_union_future_size {
+    ($t:ty, max_bytes: $max:expr) => (
+        const _: () = assert!(::std::mem::size_of::<$t>() <= $max,
+                              "union future exceeds expected size");
+    );
+}
+
+#[cfg(feature = "futures-01")]
+mod combinators;
+#[cfg(feature = "futures-io")]
+mod io;
+mod methods;
+#[cfg(feature = "futures-01")]
+mod mock;
+#[cfg(feature = "futures-01")]
+mod sink;
+mod std_future;
+#[cfg(feature = "futures-01")]
+mod stream;
+#[cfg(feature = "test-utils")]
+mod test_utils;
+mod traits;
+
+#[cfg(feature = "derive")]
+pub use union_future_derive::UnionFuture;
+#[cfg(feature = "test-utils")]
+pub use test_utils::block_on_union;
+#[cfg(feature = "futures-01")]
+pub use combinators::{AndThen, CatchPanic, ErrInto, Flatten, FromErr, Inspect, InspectErr, MapErr,
+                      MapOk, OkInto, OnceFuture, OrElse, Race, Then, UnionFuture, UnionFutureExt,
+                      Zip};
+
+#[cfg(all(test, feature = "futures-01"))]
 #[allow(dead_code)]
 mod tests {
     extern crate futures;
     use futures::*;
     use futures::future::*;
 
-    #[derive(PartialEq, Debug, Eq)]
+    #[derive(PartialEq, Debug, Eq, Clone)]
     pub enum Error {
         Fail,
         BigFail,
@@ -221,4 +1133,720 @@ mod tests {
         let mut b: TestFut = ok::<f32, OtherError>(5.25f32).into();
         assert_eq!(Ok(Async::Ready(5.25f64)), b.poll());
     }
+
+    #[test]
+    fn generic_types() {
+        union_future!(TestFut<T, E> where T: Clone, E: From<Error>,
+                Forever => Empty<T, E>,
+                Immediate => FutureResult<T, Error>);
+
+        let mut a: TestFut<u64, Error> = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+        let mut b: TestFut<String, Error> = empty::<String, Error>().into();
+        assert_eq!(Ok(Async::NotReady), b.poll());
+        let mut c: TestFut<Vec<u8>, Error> = err::<Vec<u8>, Error>(Error::Fail).into();
+        assert_eq!(Err(Error::Fail), c.poll());
+    }
+
+    #[cfg(feature = "compat")]
+    #[test]
+    fn compat() {
+        use std::future::Future as StdFuture;
+        use std::task::{Context, Poll, Waker};
+
+        union_future!(TestFut<u64, Error>,
+                Forever => Empty<u64, Error>,
+                Immediate => FutureResult<u64, Error>);
+
+        let mut cx = Context::from_waker(Waker::noop());
+        let a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Poll::Ready(Ok(5u64)), Box::pin(a.compat()).as_mut().poll(&mut cx));
+        let b: TestFut = empty::<u64, Error>().into();
+        assert_eq!(Poll::Pending, Box::pin(b.compat()).as_mut().poll(&mut cx));
+    }
+
+    #[test]
+    fn trailing_where_clause() {
+        union_future!(TestFut<T, E> where T: Send + 'static,
+                Forever => Empty<T, E>,
+                Immediate => FutureResult<T, Error>
+                where E: From<Error>);
+
+        let mut a: TestFut<u64, Error> = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+        let mut b: TestFut<Vec<u8>, Error> = empty::<Vec<u8>, Error>().into();
+        assert_eq!(Ok(Async::NotReady), b.poll());
+    }
+
+    #[test]
+    fn const_count() {
+        union_future!(TestFut<const N: usize, u64, Error>,
+                Forever => Empty<u64, Error>,
+                Immediate => FutureResult<u64, Error>);
+
+        let counts = [0u8; TestFut::<2>::COUNT];
+        assert_eq!(2, counts.len());
+
+        let mut a: TestFut<2> = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+    }
+
+    #[test]
+    fn no_from() {
+        union_future!(TestFut<u64, Error>,
+                Fast => FutureResult<u64, Error>,
+                #[no_from]
+                Slow => FutureResult<u64, Error>);
+
+        let mut a: TestFut = ok::<u64, Error>(5).into();
+        assert!(a.is_fast());
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+        let mut b = TestFut::Slow(ok::<u64, Error>(6));
+        assert_eq!(Ok(Async::Ready(6u64)), b.poll());
+    }
+
+    #[test]
+    fn err_closure() {
+        union_future!(TestFut<u64, Error>,
+                Cached => FutureResult<u64, Error>,
+                Remote => FutureResult<u64, String>, err: |_: String| Error::Fail,
+                Floating => FutureResult<u64, OtherError>, err: |e: OtherError| match e.op {
+                    0 => Error::Fail,
+                    _ => Error::BigFail,
+                });
+
+        let mut a: TestFut = err::<u64, String>(String::from("timeout")).into();
+        assert_eq!(Err(Error::Fail), a.poll());
+        let mut b: TestFut = err::<u64, OtherError>(OtherError { op: 1 }).into();
+        assert_eq!(Err(Error::BigFail), b.poll());
+        let mut c: TestFut = ok::<u64, String>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), c.poll());
+    }
+
+    #[test]
+    fn single_variant() {
+        union_future!(TestFut<u64, Error>,
+                Only => FutureResult<u64, Error>);
+
+        let mut a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+        assert_eq!(1, TestFut::VARIANT_COUNT);
+
+        // converting into the enum and back out gives the same future
+        let b: TestFut = err::<u64, Error>(Error::Fail).into();
+        let mut only = b.into_only().unwrap();
+        assert_eq!(Err(Error::Fail), only.poll());
+    }
+
+    #[test]
+    fn zero_variants() {
+        union_future!(Never<u64, Error>);
+
+        fn assert_future<F: Future<Item = u64, Error = Error>>(f: Option<F>) -> bool {
+            f.is_some()
+        }
+        assert!(!assert_future::<Never>(None));
+        assert_eq!(0, Never::VARIANT_COUNT);
+    }
+
+    #[test]
+    fn impl_only() {
+        #[derive(Debug)]
+        enum TestFut<F> {
+            Cached(FutureResult<u64, Error>),
+            Other(F),
+        }
+
+        union_future_impl!(TestFut<u64, Error>,
+                #[no_from]
+                Cached => FutureResult<u64, Error>,
+                Other => F
+                where F: Future<Item = u64, Error = Error>);
+
+        let mut a: TestFut<Empty<u64, Error>> = TestFut::Cached(ok(5));
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+        let mut b: TestFut<Empty<u64, Error>> = empty().into();
+        assert_eq!(Ok(Async::NotReady), b.poll());
+        assert!(format!("{:?}", a).starts_with("Cached("));
+    }
+
+    #[test]
+    fn merge() {
+        union_future!(ReadFuture<u64, Error>,
+                Cached => FutureResult<u64, Error>,
+                Forever => Empty<u64, Error>);
+        union_future!(WriteFuture<u32, Error>,
+                Written => FutureResult<u32, Error>);
+        union_future_merge!(RwFuture<u64, Error> = ReadFuture + WriteFuture);
+
+        let mut a: RwFuture = ReadFuture::from(ok::<u64, Error>(5)).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+        let mut b: RwFuture = WriteFuture::from(ok::<u32, Error>(6)).into();
+        assert_eq!(Ok(Async::Ready(6u64)), b.poll());
+        assert_eq!(2, RwFuture::VARIANT_COUNT);
+        assert!(b.is_write_future());
+    }
+
+    #[test]
+    fn split() {
+        use std::convert::TryFrom;
+
+        union_future!(TestFut<u64, Error>,
+                Cached => FutureResult<u64, Error>,
+                Small => FutureResult<u32, Error>,
+                Forever => Empty<u64, Error>);
+        union_future_split!(TestFut<u64, Error> =>
+                (FastFut contains Cached => FutureResult<u64, Error>
+                                | Small => FutureResult<u32, Error>),
+                (SlowFut contains Forever => Empty<u64, Error>));
+
+        let a: TestFut = ok::<u32, Error>(5).into();
+        let mut fast = FastFut::try_from(a).ok().unwrap();
+        assert_eq!(Ok(Async::Ready(5u64)), fast.poll());
+
+        let b: TestFut = empty().into();
+        let b = FastFut::try_from(b).err().unwrap();
+        assert!(b.is_forever());
+        let mut slow = SlowFut::try_from(b).ok().unwrap();
+        assert_eq!(Ok(Async::NotReady), slow.poll());
+    }
+
+    #[test]
+    fn split_attributes_and_visibility() {
+        use std::convert::TryFrom;
+
+        union_future!(TestFut<u64, Error>,
+                Cached => FutureResult<u64, Error>,
+                Forever => Empty<u64, Error>);
+        union_future_split!(
+                /// A union future split from `TestFut`.
+                #[derive(Debug)]
+                pub(crate) TestFut<u64, Error> =>
+                (FastFut contains Cached => FutureResult<u64, Error>),
+                (SlowFut contains Forever => Empty<u64, Error>),);
+
+        let a: TestFut = ok::<u64, Error>(5).into();
+        let fast = FastFut::try_from(a).ok().unwrap();
+        assert!(format!("{:?}", fast).starts_with("Cached("));
+        let b: TestFut = empty().into();
+        let slow = SlowFut::try_from(b).ok().unwrap();
+        assert!(format!("{:?}", slow).starts_with("Forever("));
+    }
+
+    #[test]
+    fn boxed_variant() {
+        union_future!(TestFut<u64, Error>,
+                Immediate => FutureResult<u64, Error>,
+                Boxed => Box<dyn Future<Item = u64, Error = Error> + Send>);
+
+        let boxed: Box<dyn Future<Item = u64, Error = Error> + Send> =
+            Box::new(ok::<u32, Error>(5).map(u64::from));
+        let mut a: TestFut = boxed.into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+        let mut b = TestFut::Boxed(Box::new(empty()));
+        assert_eq!(Ok(Async::NotReady), b.poll());
+        let mut c: TestFut = ok::<u64, Error>(6).into();
+        assert_eq!(Ok(Async::Ready(6u64)), c.poll());
+    }
+
+    #[test]
+    fn either() {
+        union_future!(TestFut<u64, Error>,
+                Immediate => FutureResult<u64, Error>,
+                Forever => Empty<u64, Error>);
+
+        let mut a: TestFut = Either::A(ok::<u64, Error>(5)).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+        let b: TestFut = Either::B(empty()).into();
+        assert!(b.is_forever());
+
+        let c: TestFut = ok::<u64, Error>(6).into();
+        let mut either: Either<FutureResult<u64, Error>, Empty<u64, Error>> = c.into();
+        assert_eq!(Ok(Async::Ready(6u64)), either.poll());
+    }
+
+    #[test]
+    fn trailing_comma() {
+        union_future!(TestFut<u64, Error>,
+                Cached => FutureResult<u64, Error>,
+                Forever => Empty<u64, Error>,);
+        union_future!(ErrFut<u64, Error>,
+                Cached => FutureResult<u64, Error>,
+                Remote => FutureResult<u64, String>, err: |_: String| Error::Fail,);
+        union_future!(GenericFut<u64, Error>,
+                #[no_from]
+                Cached => FutureResult<u64, Error>,
+                Other => F
+                where F: Future<Item = u64, Error = Error>,);
+
+        let mut a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+        let mut b: ErrFut = err::<u64, String>(String::from("timeout")).into();
+        assert_eq!(Err(Error::Fail), b.poll());
+        let mut c: GenericFut<Empty<u64, Error>> = empty().into();
+        assert_eq!(Ok(Async::NotReady), c.poll());
+    }
+
+    #[test]
+    fn infallible_variants() {
+        use std::convert::Infallible;
+
+        union_future!(TestFut<u64, Error>,
+                Cached(infallible) => FutureResult<u64, Infallible>,
+                Remote => FutureResult<u64, Error>);
+
+        let mut a: TestFut = ok::<u64, Infallible>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+        let mut b: TestFut = err::<u64, Error>(Error::Fail).into();
+        assert_eq!(Err(Error::Fail), b.poll());
+    }
+
+    #[cfg(feature = "nightly")]
+    #[test]
+    fn never_error() {
+        union_future_infallible!(TestFut<u64>,
+                Cached => FutureResult<u64, !>,
+                Computed => Map<FutureResult<u32, !>, fn(u32) -> u64>);
+
+        let mut a: TestFut = ok::<u64, !>(5).into();
+        let r: Result<Async<u64>, !> = a.poll();
+        assert_eq!(Ok(Async::Ready(5u64)), r);
+        let mut b: TestFut = ok::<u32, !>(6).map(u64::from as fn(u32) -> u64).into();
+        assert_eq!(Ok(Async::Ready(6u64)), b.poll());
+    }
+
+    #[test]
+    fn track_caller() {
+        use std::panic::Location;
+
+        pub struct CallerLine;
+
+        impl Future for CallerLine {
+            type Item = u32;
+            type Error = Error;
+
+            #[track_caller]
+            fn poll(&mut self) -> Poll<u32, Error> {
+                Ok(Async::Ready(Location::caller().line()))
+            }
+        }
+
+        union_future!(TestFut<u32, Error>,
+                Caller => CallerLine,
+                Number => FutureResult<u32, Error>);
+
+        let mut a: TestFut = CallerLine.into();
+        let (line, poll) = (line!(), a.poll());
+        assert_eq!(Ok(Async::Ready(line)), poll);
+    }
+
+    #[test]
+    fn inline() {
+        union_future!(#[inline] TestFut<u64, Error>,
+                Forever => Empty<u64, Error>,
+                Immediate => FutureResult<u64, Error>);
+
+        let mut a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+    }
+
+    #[test]
+    fn assert_size() {
+        union_future!(TestFut<u64, Error>,
+                Forever => Empty<u64, Error>,
+                Immediate => FutureResult<u64, Error>);
+
+        assert_union_future_size!(TestFut, max_bytes: 32);
+        assert_union_future_size!(TestFut, max_bytes: ::std::mem::size_of::<TestFut>());
+    }
+
+    #[test]
+    fn hot_cold_variants() {
+        union_future!(TestFut<u64, Error>,
+                #[hot]
+                Cached => FutureResult<u64, Error>,
+                Forever => Empty<u64, Error>,
+                #[cold]
+                #[no_from]
+                Slow => FutureResult<u64, Error>);
+
+        let mut a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+        let mut b: TestFut = empty::<u64, Error>().into();
+        assert_eq!(Ok(Async::NotReady), b.poll());
+        let mut c = TestFut::Slow(err::<u64, Error>(Error::Fail));
+        assert_eq!(Err(Error::Fail), c.poll());
+    }
+
+    #[test]
+    fn attributes() {
+        union_future!(
+            /// A future which is either ready or never is.
+            #[derive(Debug)]
+            #[derive(Clone)]
+            TestFut<u64, Error>,
+                Small => FutureResult<u32, Error>,
+                Immediate => FutureResult<u64, Error>);
+
+        let a: TestFut = ok::<u64, Error>(5).into();
+        let mut b = a.clone();
+        assert_eq!(Ok(Async::Ready(5u64)), b.poll());
+        assert!(format!("{:?}", a).starts_with("Immediate("));
+    }
+
+    #[test]
+    fn cfg_attr_attributes() {
+        union_future!(
+            #[cfg_attr(test, repr(u8))]
+            #[cfg_attr(not(test), derive(NotATrait))]
+            TestFut<u64, Error>,
+                Small => FutureResult<u32, Error>,
+                Immediate => FutureResult<u64, Error>);
+
+        let a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(1, unsafe { *(&a as *const TestFut as *const u8) });
+    }
+
+    #[test]
+    fn non_exhaustive() {
+        union_future!(
+            #[non_exhaustive]
+            TestFut<u64, Error>,
+                Forever => Empty<u64, Error>,
+                Immediate => FutureResult<u64, Error>);
+
+        let mut a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+    }
+
+    #[test]
+    fn repr_u8() {
+        union_future!(
+            #[repr(u8)]
+            TestFut<u64, Error>,
+                Forever => Empty<u64, Error>,
+                Immediate => FutureResult<u64, Error>);
+
+        fn discriminant(f: &TestFut) -> u8 {
+            unsafe { *(f as *const TestFut as *const u8) }
+        }
+
+        let a: TestFut = empty::<u64, Error>().into();
+        assert_eq!(0, discriminant(&a));
+        let mut b: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(1, discriminant(&b));
+        assert_eq!(Ok(Async::Ready(5u64)), b.poll());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated() {
+        union_future!(
+            #[deprecated = "use a different future instead"]
+            TestFut<u64, Error>,
+                Forever => Empty<u64, Error>,
+                Immediate => FutureResult<u64, Error>);
+
+        let mut a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+    }
+
+    #[test]
+    fn deprecated_variants() {
+        union_future!(TestFut<u64, Error>,
+                #[deprecated = "use Immediate instead"]
+                Forever => Empty<u64, Error>,
+                Immediate => FutureResult<u64, Error>);
+
+        let mut a: TestFut = empty::<u64, Error>().into();
+        assert_eq!(Ok(Async::NotReady), a.poll());
+        let mut b: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), b.poll());
+    }
+
+    #[test]
+    fn cfg_variants() {
+        union_future!(TestFut<const N: usize, u64, Error>,
+                #[cfg(not(test))]
+                Missing => Empty<u64, Error>,
+                /// The future which is always ready.
+                Immediate => FutureResult<u64, Error>,
+                #[cfg(test)]
+                Forever => Empty<u64, Error>);
+
+        assert_eq!(2, TestFut::<2>::COUNT);
+
+        let mut a: TestFut<2> = empty::<u64, Error>().into();
+        assert_eq!(Ok(Async::NotReady), a.poll());
+        let mut b: TestFut<2> = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), b.poll());
+    }
+
+    #[test]
+    fn infallible() {
+        use std::convert::Infallible;
+
+        union_future!(TestFut<u64, !>,
+                Forever => Empty<u64, Infallible>,
+                Immediate => FutureResult<u64, Infallible>);
+
+        let mut a: TestFut = ok::<u64, Infallible>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+        let _: fn(&mut TestFut) -> Poll<u64, Infallible> = Future::poll;
+    }
+
+    #[test]
+    fn lifetimes() {
+        use std::borrow::Cow;
+
+        union_future!(TestFut<'a, Cow<'a, str>, Error>,
+                Borrowed => FutureResult<&'a str, Error>,
+                Owned => FutureResult<String, Error>);
+
+        let s = String::from("borrowed");
+        let mut a: TestFut = ok::<&str, Error>(&s).into();
+        assert_eq!(Ok(Async::Ready(Cow::Borrowed("borrowed"))), a.poll());
+        let mut b: TestFut = ok::<String, Error>(String::from("owned")).into();
+        assert_eq!(Ok(Async::Ready(Cow::Owned(String::from("owned")))), b.poll());
+    }
+
+    #[test]
+    fn generic_lifetimes() {
+        union_future!(TestFut<'a, &'a T, Error> where T: 'a,
+                Borrowed => FutureResult<&'a T, Error>,
+                Forever => Empty<&'a T, Error>);
+
+        let v = 5u64;
+        let mut a: TestFut<u64> = ok::<&u64, Error>(&v).into();
+        assert_eq!(Ok(Async::Ready(&5u64)), a.poll());
+    }
+
+    #[test]
+    fn crate_visibility() {
+        union_future!(pub(crate) TestFut<u64, Error>,
+                Forever => Empty<u64, Error>,
+                Immediate => FutureResult<u64, Error>);
+
+        let mut a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+    }
+
+    mod parent {
+        use futures::future::*;
+        use super::Error;
+
+        union_future!(pub(super) TestFut<u64, Error>,
+                Forever => Empty<u64, Error>,
+                Immediate => FutureResult<u64, Error>);
+    }
+
+    mod outer {
+        pub mod inner {
+            use futures::future::*;
+            use tests::Error;
+
+            union_future!(pub(in crate::tests::outer) TestFut<u64, Error>,
+                    Forever => Empty<u64, Error>,
+                    Immediate => FutureResult<u64, Error>);
+        }
+
+        pub fn immediate(v: u64) -> ::futures::Poll<u64, ::tests::Error> {
+            use futures::Future;
+            let mut f: inner::TestFut = ::futures::future::ok(v).into();
+            f.poll()
+        }
+    }
+
+    #[test]
+    fn super_visibility() {
+        let mut a: parent::TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+    }
+
+    #[test]
+    fn restricted_visibility() {
+        assert_eq!(Ok(Async::Ready(5u64)), outer::immediate(5));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derive() {
+        #[derive(::UnionFuture)]
+        #[future(item = u64, error = Error)]
+        enum TestFut {
+            #[future_variant]
+            Small(FutureResult<u32, Error>),
+            #[future_variant]
+            Forever(Empty<u64, Error>),
+        }
+
+        let mut a: TestFut = ok::<u32, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5u64)), a.poll());
+        let mut b: TestFut = empty().into();
+        assert_eq!(Ok(Async::NotReady), b.poll());
+    }
+
+    // a future whose behavior is chosen by the property tests
+    pub enum Scripted<T, E> {
+        Ready(Option<T>),
+        Never,
+        Fail(Option<E>),
+    }
+
+    impl<T, E> Future for Scripted<T, E> {
+        type Item = T;
+        type Error = E;
+
+        fn poll(&mut self) -> Poll<T, E> {
+            match *self {
+                Scripted::Ready(ref mut t) => Ok(Async::Ready(t.take().expect("polled twice"))),
+                Scripted::Never => Ok(Async::NotReady),
+                Scripted::Fail(ref mut e) => Err(e.take().expect("polled twice")),
+            }
+        }
+    }
+
+    union_future!(PropFut<u64, Error>,
+            Wide => Scripted<u64, Error>,
+            Narrow => Scripted<u32, Error>,
+            Other => Scripted<u8, OtherError>);
+
+    const VARIANTS: u8 = 3;
+
+    fn ready_variant(index: u8, value: u8) -> PropFut {
+        match index % VARIANTS {
+            0 => Scripted::Ready(Some(u64::from(value))).into(),
+            1 => Scripted::Ready(Some(u32::from(value))).into(),
+            _ => Scripted::<u8, OtherError>::Ready(Some(value)).into(),
+        }
+    }
+
+    fn never_variant(index: u8) -> PropFut {
+        match index % VARIANTS {
+            0 => Scripted::<u64, Error>::Never.into(),
+            1 => Scripted::<u32, Error>::Never.into(),
+            _ => Scripted::<u8, OtherError>::Never.into(),
+        }
+    }
+
+    fn failing_variant(index: u8) -> (PropFut, Error) {
+        match index % VARIANTS {
+            0 => (Scripted::<u64, Error>::Fail(Some(Error::Fail)).into(), Error::Fail),
+            1 => (Scripted::<u32, Error>::Fail(Some(Error::Fail)).into(), Error::Fail),
+            _ => (Scripted::<u8, OtherError>::Fail(Some(OtherError { op: 1 })).into(),
+                  Error::BigFail),
+        }
+    }
+
+    quickcheck! {
+        fn prop_ready_on_first_poll(index: u8, value: u8) -> bool {
+            ready_variant(index, value).poll() == Ok(Async::Ready(u64::from(value)))
+        }
+
+        fn prop_never_ready(index: u8, polls: u8) -> bool {
+            let mut f = never_variant(index);
+            (0..=polls).all(|_| f.poll() == Ok(Async::NotReady))
+        }
+
+        fn prop_error_converted(index: u8) -> bool {
+            let (mut f, e) = failing_variant(index);
+            f.poll() == Err(e)
+        }
+    }
+
+    #[test]
+    fn hygiene_locals() {
+        let f = 1u64;
+        let e = Error::BigFail;
+        let t = 2u64;
+        #[allow(non_snake_case)]
+        let Cached = 3u64;
+
+        union_future!(TestFut<u64, Error>,
+                Cached => FutureResult<u64, Error>,
+                Db => FutureResult<u32, OtherError>);
+
+        let mut a: TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5)), a.poll());
+        let mut b: TestFut = err::<u32, OtherError>(OtherError { op: 1 }).into();
+        assert_eq!(Err(Error::BigFail), b.poll());
+        assert_eq!((1, Error::BigFail, 2, 3), (f, e, t, Cached));
+    }
+
+    #[test]
+    fn hygiene_self() {
+        pub struct Query(u64);
+
+        impl Query {
+            fn run(&self) -> Poll<u64, Error> {
+                union_future!(TestFut<u64, Error>,
+                        Cached => FutureResult<u64, Error>,
+                        Db => Empty<u64, Error>);
+
+                let mut f: TestFut = ok::<u64, Error>(self.0).into();
+                f.poll()
+            }
+        }
+
+        assert_eq!(Ok(Async::Ready(5)), Query(5).run());
+    }
+
+    // the prelude names and the futures crate are shadowed where the macro is invoked
+    mod hygiene {
+        #![allow(dead_code, non_upper_case_globals)]
+        pub type Result<T> = ::std::result::Result<T, ()>;
+        pub struct Ok<T>(pub T);
+        pub struct Err<T>(pub T);
+        pub type Option<T> = Result<T>;
+        pub struct Some<T>(pub T);
+        pub const None: () = ();
+        pub trait From<T> {}
+        pub struct Box;
+        pub trait Default {}
+        pub trait Send {}
+        pub trait FnOnce {}
+        pub trait FnMut {}
+        pub mod futures {}
+        pub mod std {}
+
+        union_future!(pub TestFut<u64, super::Error>,
+                Cached => ::futures::future::FutureResult<u64, super::Error>,
+                Db => ::futures::future::Empty<u64, super::Error>);
+
+        union_future_split!(TestFut<u64, super::Error> =>
+                (CachedFut contains Cached => ::futures::future::FutureResult<u64, super::Error>),
+                (DbFut contains Db => ::futures::future::Empty<u64, super::Error>));
+
+        ::mock_future!(pub MockFut<u64, super::Error> {
+            resolves_after: 1,
+            value: 5,
+            error_on: 2,
+            error: super::Error::Fail,
+        });
+
+        ::union_future_03!(pub TestFut03<u64, super::Error>,
+                Cached => ::std::future::Ready<::std::result::Result<u64, super::Error>>);
+
+        ::union_future_std!(pub TestFutStd<u64>,
+                Cached => ::std::future::Ready<u32>);
+
+        ::union_stream!(pub TestStream<u64, super::Error>,
+                Iter => ::futures::stream::IterOk<::std::vec::IntoIter<u64>, super::Error>);
+
+        ::union_sink!(pub TestSink<u64, ()>,
+                Memory => Vec<u64>);
+    }
+
+    #[test]
+    fn hygiene_shadowed_names() {
+        let mut a: hygiene::TestFut = ok::<u64, Error>(5).into();
+        assert_eq!(Ok(Async::Ready(5)), a.poll());
+        let mut b: hygiene::TestFut = empty::<u64, Error>().into();
+        assert_eq!(Ok(Async::NotReady), b.poll());
+        assert!(b.as_db().is_some());
+        assert!(b.into_cached().is_none());
+        let mut c = hygiene::MockFut::new();
+        assert_eq!(Ok(Async::NotReady), c.poll());
+        assert_eq!(Ok(Async::Ready(5)), c.poll());
+    }
 }
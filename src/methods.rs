@@ -0,0 +1,206 @@
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_methods {
+    ($name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        $crate::__paste::paste! {
+            #[allow(dead_code, deprecated)]
+            impl<$($g)*> $name<$($u)*> where $($w)* {
+                /// The number of variants.
+                pub const VARIANT_COUNT: usize = {
+                    let count = 0;
+                    $( $($cfg)* let count = count + 1; )*
+                    count
+                };
+
+                /// The size of the enum in bytes, which is the size of its largest variant plus
+                /// the discriminant rounded up to the alignment of the enum, unless the
+                /// discriminant fits in a niche of a variant.
+                pub const SIZE: usize = ::std::mem::size_of::<Self>();
+
+                /// Returns the name of the active variant, as written in the macro invocation.
+                pub fn variant_name(&self) -> &'static str {
+                    match *self {
+                        $( $($cfg)* $name::$n(_) => stringify!($n), )*
+                    }
+                }
+
+                /// Returns the index of the active variant, counting from zero in the order of
+                /// the variants in the macro invocation.
+                pub fn variant_index(&self) -> usize {
+                    // the discriminants of a fieldless enum are the indices of its variants,
+                    // skipping the variants which are disabled by `cfg` attributes
+                    enum Index {
+                        $( $($cfg)* $n, )*
+                    }
+
+                    match *self {
+                        $( $($cfg)* $name::$n(_) => Index::$n as usize, )*
+                    }
+                }
+
+                $(
+                    $($cfg)*
+                    #[doc = concat!("Returns `true` if this is the `", stringify!($n),
+                                    "` variant.")]
+                    pub fn [<is_ $n:snake>](&self) -> bool {
+                        match *self {
+                            $name::$n(_) => true,
+                            #[allow(unreachable_patterns)]
+                            _ => false,
+                        }
+                    }
+
+                    $($cfg)*
+                    #[doc = concat!("Returns the inner value of the `", stringify!($n),
+                                    "` variant, or `None` if another variant is active.")]
+                    pub fn [<into_ $n:snake>](self) -> ::std::option::Option<$ft> {
+                        match self {
+                            $name::$n(inner) => ::std::option::Option::Some(inner),
+                            #[allow(unreachable_patterns)]
+                            _ => ::std::option::Option::None,
+                        }
+                    }
+
+                    $($cfg)*
+                    #[doc = concat!("Returns a reference to the inner value of the `",
+                                    stringify!($n),
+                                    "` variant, or `None` if another variant is active.")]
+                    pub fn [<as_ $n:snake>](&self) -> ::std::option::Option<&$ft> {
+                        match *self {
+                            $name::$n(ref inner) => ::std::option::Option::Some(inner),
+                            #[allow(unreachable_patterns)]
+                            _ => ::std::option::Option::None,
+                        }
+                    }
+
+                    $($cfg)*
+                    #[doc = concat!("Returns a mutable reference to the inner value of the `",
+                                    stringify!($n),
+                                    "` variant, or `None` if another variant is active.")]
+                    pub fn [<as_mut_ $n:snake>](&mut self) -> ::std::option::Option<&mut $ft> {
+                        match *self {
+                            $name::$n(ref mut inner) => ::std::option::Option::Some(inner),
+                            #[allow(unreachable_patterns)]
+                            _ => ::std::option::Option::None,
+                        }
+                    }
+                )*
+            }
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::{pending, ready, Pending, Ready};
+
+    #[test]
+    fn variant_name() {
+        ::union_future_std!(TestFut<u64>,
+                Forever => Pending<u64>,
+                IoUring => Ready<u64>);
+
+        let a: TestFut = pending::<u64>().into();
+        assert_eq!("Forever", a.variant_name());
+        let b: TestFut = ready(5u64).into();
+        assert_eq!("IoUring", b.variant_name());
+    }
+
+    #[test]
+    fn variant_index() {
+        ::union_future_std!(TestFut<u64>,
+                Forever => Pending<u64>,
+                #[cfg(not(test))]
+                Missing => Ready<u32>,
+                Immediate => Ready<u64>);
+
+        let a: TestFut = pending::<u64>().into();
+        assert_eq!(0, a.variant_index());
+        let b: TestFut = ready(5u64).into();
+        assert_eq!(1, b.variant_index());
+    }
+
+    #[test]
+    fn variant_count() {
+        ::union_future_std!(TestFut<u64>,
+                Forever => Pending<u64>,
+                #[cfg(not(test))]
+                Missing => Ready<u32>,
+                Immediate => Ready<u64>);
+
+        let counters = [0u64; TestFut::VARIANT_COUNT];
+        assert_eq!(2, counters.len());
+    }
+
+    #[test]
+    fn size() {
+        ::union_future_std!(TestFut<u64>,
+                Forever => Pending<u64>,
+                Immediate => Ready<u64>);
+
+        assert_eq!(::std::mem::size_of::<TestFut>(), TestFut::SIZE);
+        assert!(TestFut::SIZE <= ::std::mem::size_of::<Ready<u64>>() + 8);
+    }
+
+    #[test]
+    fn is_variant() {
+        ::union_future_std!(TestFut<u64>,
+                Forever => Pending<u64>,
+                Immediate => Ready<u64>,
+                IoUring => Ready<u32>);
+
+        let a: TestFut = pending::<u64>().into();
+        assert!(a.is_forever());
+        assert!(!a.is_immediate());
+        let b: TestFut = ready(5u64).into();
+        assert!(!b.is_forever());
+        assert!(b.is_immediate());
+        let c: TestFut = ready(5u32).into();
+        assert!(c.is_io_uring());
+    }
+
+    #[test]
+    fn into_variant() {
+        ::union_future_std!(TestFut<u64>,
+                Forever => Pending<u64>,
+                Immediate => Ready<u64>);
+
+        let a: TestFut = ready(5u64).into();
+        assert!(a.into_forever().is_none());
+        let b: TestFut = ready(5u64).into();
+        assert_eq!(Some(5), b.into_immediate().map(Ready::into_inner));
+    }
+
+    #[test]
+    fn as_variant() {
+        ::union_future_std!(TestFut<String>,
+                Forever => Pending<String>,
+                Immediate => Ready<String>);
+
+        let a: TestFut = ready(String::from("value")).into();
+        assert!(a.as_forever().is_none());
+        assert!(a.as_immediate().is_some());
+        assert!(a.is_immediate());
+    }
+
+    #[test]
+    fn as_mut_variant() {
+        ::union_future_std!(TestFut<u64>,
+                Forever => Pending<u64>,
+                Immediate => Ready<u64>);
+
+        let mut a: TestFut = pending::<u64>().into();
+        assert!(a.as_mut_immediate().is_none());
+        if let Some(f) = a.as_mut_forever() {
+            *f = pending();
+        }
+        assert!(a.is_forever());
+
+        let mut b: TestFut = ready(5u64).into();
+        if let Some(f) = b.as_mut_immediate() {
+            *f = ready(6);
+        }
+        assert_eq!(Some(6), b.into_immediate().map(Ready::into_inner));
+    }
+}
@@ -0,0 +1,237 @@
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __mock_future {
+    (@impl [$($attrs:tt)*] [$($vis:tt)*] $name:ident [$item:ty, $err:ty]
+     [$resolves_after:expr] [$value:expr] [$error_on:expr] [$error:expr]) => (
+        $($attrs)*
+        $($vis)* struct $name {
+            polls: usize,
+            resolves_after: usize,
+            error_on: ::std::option::Option<usize>,
+            value: ::std::option::Option<$item>,
+            error: ::std::option::Option<$err>,
+        }
+
+        #[allow(dead_code)]
+        impl $name {
+            /// Creates the mock future, which has not been polled yet.
+            pub fn new() -> $name {
+                $name {
+                    polls: 0,
+                    resolves_after: $resolves_after,
+                    error_on: $error_on,
+                    value: ::std::option::Option::Some($value),
+                    error: $error,
+                }
+            }
+
+            /// The number of times the mock future has been polled.
+            pub fn polls(&self) -> usize {
+                self.polls
+            }
+        }
+
+        impl ::std::default::Default for $name {
+            fn default() -> $name {
+                $name::new()
+            }
+        }
+
+        impl $crate::__futures::Future for $name {
+            type Item = $item;
+            type Error = $err;
+
+            fn poll(&mut self) -> $crate::__futures::Poll<$item, $err> {
+                self.polls += 1;
+                match self.error_on {
+                    ::std::option::Option::Some(error_on) if self.polls > error_on => {
+                        let error = self.error.take();
+                        return ::std::result::Result::Err(
+                            error.expect(concat!(stringify!($name), " polled after it failed")));
+                    }
+                    _ => {}
+                }
+                if self.polls > self.resolves_after {
+                    let value = self.value.take();
+                    ::std::result::Result::Ok($crate::__futures::Async::Ready(
+                        value.expect(concat!(stringify!($name), " polled after it resolved"))))
+                } else {
+                    // the mock future is ready to be polled again right away
+                    if $crate::__futures::task::is_in_task() {
+                        $crate::__futures::task::current().notify();
+                    }
+                    ::std::result::Result::Ok($crate::__futures::Async::NotReady)
+                }
+            }
+        }
+    );
+    (@fields $attrs:tt $vis:tt $name:ident [$item:ty, $err:ty]
+     { resolves_after: $resolves_after:expr, value: $value:expr,
+       error_on: $error_on:expr, error: $error:expr $(,)* }) => (
+        $crate::__mock_future!(@impl $attrs $vis $name [$item, $err]
+                               [$resolves_after] [$value] [::std::option::Option::Some($error_on)]
+                               [::std::option::Option::Some($error)]);
+    );
+    (@fields $attrs:tt $vis:tt $name:ident [$item:ty, $err:ty]
+     { resolves_after: $resolves_after:expr, value: $value:expr,
+       error_on: $error_on:expr $(,)* }) => (
+        $crate::__mock_future!(@impl $attrs $vis $name [$item, $err]
+                               [$resolves_after] [$value] [::std::option::Option::Some($error_on)]
+                               [::std::option::Option::Some(::std::default::Default::default())]);
+    );
+    (@fields $attrs:tt $vis:tt $name:ident [$item:ty, $err:ty]
+     { resolves_after: $resolves_after:expr, value: $value:expr $(,)* }) => (
+        $crate::__mock_future!(@impl $attrs $vis $name [$item, $err]
+                               [$resolves_after] [$value] [::std::option::Option::None]
+                               [::std::option::Option::None]);
+    );
+    ($attrs:tt $vis:tt $name:ident [] [] [] [$item:ty] $fields:tt) => (
+        $crate::__mock_future!(@fields $attrs $vis $name [$item, ()] $fields);
+    );
+    ($attrs:tt $vis:tt $name:ident [] [] [] [$item:ty, $err:ty] $fields:tt) => (
+        $crate::__mock_future!(@fields $attrs $vis $name [$item, $err] $fields);
+    );
+    ($attrs:tt $vis:tt $name:ident $g:tt $u:tt $w:tt $hdr:tt $fields:tt) => (
+        compile_error!(concat!("mock_future! does not support generics, `",
+                               stringify!($name), "` must only have item and error types"));
+    );
+}
+
+/// A macro to create a futures 0.1 future with a scripted behavior, for testing the union futures
+/// wrapping it.
+///
+/// The generated struct counts how many times it is polled. It is not ready for the first
+/// `resolves_after` polls and then resolves to `value`, unless it fails first: with `error_on`,
+/// the poll following the first `error_on` polls fails with `error`, which defaults to the
+/// `Default` value of the error type. Polling the mock future again after it resolved or failed
+/// panics, like most futures do.
+///
+/// The struct is declared like the enum of `union_future`, with attributes, visibility, and its
+/// item type followed by its error type, which defaults to `()`. The generated struct is `pub`
+/// unless a visibility modifier is given. The fields follow in braces.
+///
+/// When it isn't ready, the mock future notifies the current task so it is polled again, which
+/// lets it be driven by an executor such as the one of `block_on_union`, while a test may poll it
+/// directly outside of a task as well.
+///
+/// ```
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// use futures::{Async, Future};
+///
+/// mock_future!(MockFoo<u64> { resolves_after: 3, value: 42u64, error_on: 5 });
+///
+/// # fn main() {
+/// let mut f = MockFoo::new();
+/// for _ in 0..3 {
+///     assert_eq!(Ok(Async::NotReady), f.poll());
+/// }
+/// assert_eq!(Ok(Async::Ready(42)), f.poll());
+/// assert_eq!(4, f.polls());
+/// # }
+/// ```
+///
+/// The struct cannot be generic, so lifetime or const parameters are rejected:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// mock_future!(MockFoo<'a, &'a str> { resolves_after: 0, value: "borrowed" });
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! mock_future {
+    ($($args:tt)*) => (
+        $crate::__union_parse!(@start __mock_future $($args)*);
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{Async, Future};
+
+    #[derive(PartialEq, Debug, Default)]
+    pub struct MockError(u32);
+
+    #[test]
+    fn resolves_after_polls() {
+        mock_future!(MockFoo<u64> { resolves_after: 2, value: 42u64 });
+
+        let mut f = MockFoo::new();
+        assert_eq!(Ok(Async::NotReady), f.poll());
+        assert_eq!(Ok(Async::NotReady), f.poll());
+        assert_eq!(Ok(Async::Ready(42)), f.poll());
+        assert_eq!(3, f.polls());
+    }
+
+    #[test]
+    fn fails_after_polls() {
+        mock_future!(MockFoo<u64, MockError> { resolves_after: 5, value: 42u64, error_on: 1 });
+
+        let mut f = MockFoo::new();
+        assert_eq!(Ok(Async::NotReady), f.poll());
+        assert_eq!(Err(MockError(0)), f.poll());
+
+        mock_future!(MockBar<u64, MockError> {
+            resolves_after: 0,
+            value: 7,
+            error_on: 0,
+            error: MockError(3),
+        });
+        assert_eq!(Err(MockError(3)), MockBar::new().poll());
+    }
+
+    #[test]
+    #[should_panic(expected = "MockFoo polled after it failed")]
+    fn polled_after_failure() {
+        mock_future!(MockFoo<u64, MockError> { resolves_after: 5, value: 42u64, error_on: 0 });
+
+        let mut f = MockFoo::new();
+        assert_eq!(Err(MockError(0)), f.poll());
+        let _ = f.poll();
+    }
+
+    #[test]
+    fn attributes_and_visibility() {
+        mock_future!(
+            /// A mock future which resolves immediately.
+            #[derive(Debug)]
+            pub(crate) MockFoo<u64> { resolves_after: 0, value: 1 });
+
+        let mut f = MockFoo::new();
+        assert_eq!(Ok(Async::Ready(1)), f.poll());
+        assert!(format!("{:?}", f).starts_with("MockFoo {"));
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn block_on() {
+        use block_on_union;
+
+        mock_future!(MockFoo<u64, MockError> { resolves_after: 3, value: 42u64 });
+        assert_eq!(Ok(42), block_on_union(MockFoo::new()));
+
+        mock_future!(MockBar<u64, MockError> { resolves_after: 3, value: 42u64, error_on: 2 });
+        assert_eq!(Err(MockError(0)), block_on_union(MockBar::new()));
+    }
+
+    #[test]
+    fn union_of_mocks() {
+        mock_future!(pub MockFast<u32> { resolves_after: 0, value: 1 });
+        mock_future!(pub MockSlow<u64> { resolves_after: 1, value: 2, error_on: 2 });
+
+        union_future!(TestFut<u64, ()>,
+                Fast => MockFast,
+                Slow => MockSlow);
+
+        let mut a: TestFut = MockFast::new().into();
+        assert_eq!(Ok(Async::Ready(1)), a.poll());
+        let mut b: TestFut = MockSlow::new().into();
+        assert_eq!(Ok(Async::NotReady), b.poll());
+        assert_eq!(Ok(Async::Ready(2)), b.poll());
+    }
+}
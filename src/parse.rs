@@ -0,0 +1,240 @@
+// Parsing of the input shared by all of the union macros.
+//
+// `__union_parse!(@start <callback> <input>)` munches the input and invokes `$crate::<callback>!`
+// with a normalized form:
+//
+//     [<attributes>] [<visibility>] <name> [<generics>] [<generic arguments>] [<where predicates>]
+//         [<header types>]
+//         [{ [<cfg attributes>] [[<attributes>] [<options>]] <variant> <type> } ...]
+//
+// A braced body following the header, or the `where` clause following it, is passed through in
+// place of the variants.
+//
+// The header types are the types between the angle brackets following the name, such as the
+// item and error types of `union_future`, which may be preceded by lifetime parameters and a
+// `const N: usize` parameter of the generated enum. Type parameters of the generated enum are
+// declared by the `where` clauses following the header or the variants, each predicate with an
+// identifier on the left-hand side declares that identifier as a type parameter. The where
+// predicates are either empty or end with a comma, so further predicates can be appended.
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_parse {
+    (@start $cb:ident $($rest:tt)*) => (
+        $crate::__union_parse!(@attrs $cb [] $($rest)*);
+    );
+
+    // outer attributes of the generated enum
+    (@attrs $cb:ident [$($a:tt)*] # [ $($attr:tt)* ] $($rest:tt)*) => (
+        $crate::__union_parse!(@attrs $cb [$($a)* #[$($attr)*]] $($rest)*);
+    );
+    (@attrs $cb:ident $attrs:tt $($rest:tt)*) => (
+        $crate::__union_parse!(@vis [$cb $attrs] $($rest)*);
+    );
+
+    // visibility, which is `pub` when omitted
+    (@vis [$($ctx:tt)*] pub ( $($v:tt)* ) $($rest:tt)*) => (
+        $crate::__union_parse!(@name [$($ctx)* [pub($($v)*)]] $($rest)*);
+    );
+    (@vis [$($ctx:tt)*] pub $($rest:tt)*) => (
+        $crate::__union_parse!(@name [$($ctx)* [pub]] $($rest)*);
+    );
+    (@vis [$($ctx:tt)*] $($rest:tt)*) => (
+        $crate::__union_parse!(@name [$($ctx)* [pub]] $($rest)*);
+    );
+
+    // name, lifetime parameters, const parameter and header types
+    (@name [$($ctx:tt)*] $name:ident < $($rest:tt)*) => (
+        $crate::__union_parse!(@generics [$($ctx)* $name] [] $($rest)*);
+    );
+    (@name [$($ctx:tt)*] $name:ident $($rest:tt)*) => (
+        $crate::__union_parse!(@header [$($ctx)* $name [] [] []] $($rest)*);
+    );
+    (@generics $ctx:tt [$($lt:tt)*] $l:lifetime , $($rest:tt)*) => (
+        $crate::__union_parse!(@generics $ctx [$($lt)* $l] $($rest)*);
+    );
+    (@generics [$($ctx:tt)*] [$($lt:tt)*] $l:lifetime > $($rest:tt)*) => (
+        $crate::__union_parse!(@header [$($ctx)* [$($lt)* $l] [] []] $($rest)*);
+    );
+    (@generics [$($ctx:tt)*] $lts:tt const $c:ident : usize > $($rest:tt)*) => (
+        $crate::__union_parse!(@header [$($ctx)* $lts [$c] []] $($rest)*);
+    );
+    (@generics [$($ctx:tt)*] $lts:tt const $c:ident : usize , $($rest:tt)*) => (
+        $crate::__union_parse!(@types [$($ctx)* $lts [$c]] [] $($rest)*);
+    );
+    (@generics [$($ctx:tt)*] $lts:tt $($rest:tt)*) => (
+        $crate::__union_parse!(@types [$($ctx)* $lts []] [] $($rest)*);
+    );
+
+    // header types, where the never type stands for `Infallible`
+    (@types $ctx:tt [$($h:tt)*] ! , $($rest:tt)*) => (
+        $crate::__union_parse!(@types $ctx [$($h)* ::std::convert::Infallible ,] $($rest)*);
+    );
+    (@types [$($ctx:tt)*] [$($h:tt)*] ! > $($rest:tt)*) => (
+        $crate::__union_parse!(@header [$($ctx)* [$($h)* ::std::convert::Infallible]] $($rest)*);
+    );
+    (@types $ctx:tt [$($h:tt)*] $t:ty , $($rest:tt)*) => (
+        $crate::__union_parse!(@types $ctx [$($h)* $t ,] $($rest)*);
+    );
+    (@types [$($ctx:tt)*] [$($h:tt)*] $t:ty > $($rest:tt)*) => (
+        $crate::__union_parse!(@header [$($ctx)* [$($h)* $t]] $($rest)*);
+    );
+
+    // optional `where` clause following the header
+    (@header $ctx:tt where $($rest:tt)*) => (
+        $crate::__union_parse!(@where head $ctx [] [] [] + $($rest)*);
+    );
+    (@header $ctx:tt , $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx [] [] [] [] [[] []] $($rest)*);
+    );
+    (@header $ctx:tt) => (
+        $crate::__union_parse!(@variants $ctx [] [] [] [] [[] []]);
+    );
+    // a braced body in place of the variants, such as the fields of `mock_future`
+    (@header $ctx:tt { $($body:tt)* }) => (
+        $crate::__union_parse!(@done $ctx [] [] { $($body)* });
+    );
+
+    // the predicates following the header are collected until the first variant, those following
+    // the variants until the end, the `+` and `-` flags track whether the next token starts a
+    // predicate
+    (@where head $ctx:tt $params:tt [$($w:tt)*] $v:tt + , $n:ident => $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params [$($w)*] $v [] [[] []] $n => $($rest)*);
+    );
+    (@where head $ctx:tt $params:tt [$($w:tt)*] $v:tt - , $n:ident => $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params [$($w)* ,] $v [] [[] []] $n => $($rest)*);
+    );
+    (@where head $ctx:tt $params:tt [$($w:tt)*] $v:tt + , $n:ident ($m:ident) => $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params [$($w)*] $v [] [[] []] $n ($m) => $($rest)*);
+    );
+    (@where head $ctx:tt $params:tt [$($w:tt)*] $v:tt - , $n:ident ($m:ident) => $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params [$($w)* ,] $v [] [[] []]
+                               $n ($m) => $($rest)*);
+    );
+    (@where head $ctx:tt $params:tt [$($w:tt)*] $v:tt + , # $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params [$($w)*] $v [] [[] []] # $($rest)*);
+    );
+    (@where head $ctx:tt $params:tt [$($w:tt)*] $v:tt - , # $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params [$($w)* ,] $v [] [[] []] # $($rest)*);
+    );
+    (@where head $ctx:tt $params:tt [$($w:tt)*] $v:tt + { $($body:tt)* }) => (
+        $crate::__union_parse!(@done $ctx $params [$($w)*] { $($body)* });
+    );
+    (@where head $ctx:tt $params:tt [$($w:tt)*] $v:tt - { $($body:tt)* }) => (
+        $crate::__union_parse!(@done $ctx $params [$($w)* ,] { $($body)* });
+    );
+    (@where tail $ctx:tt $params:tt $w:tt $v:tt +) => (
+        $crate::__union_parse!(@hot $ctx $params $w $v);
+    );
+    (@where tail $ctx:tt $params:tt [$($w:tt)*] $v:tt -) => (
+        $crate::__union_parse!(@hot $ctx $params [$($w)* ,] $v);
+    );
+    (@where $mode:ident $ctx:tt [$($p:ident)*] [$($w:tt)*] $v:tt + $param:ident : $($rest:tt)*) => (
+        $crate::__union_parse!(@where $mode $ctx [$($p)* $param] [$($w)* $param :] $v - $($rest)*);
+    );
+    (@where $mode:ident $ctx:tt $params:tt [$($w:tt)*] $v:tt $start:tt , $($rest:tt)*) => (
+        $crate::__union_parse!(@where $mode $ctx $params [$($w)* ,] $v + $($rest)*);
+    );
+    (@where $mode:ident $ctx:tt $params:tt [$($w:tt)*] $v:tt $start:tt $t:tt $($rest:tt)*) => (
+        $crate::__union_parse!(@where $mode $ctx $params [$($w)* $t] $v - $($rest)*);
+    );
+
+    // variants, each preceded by its attributes and the last optionally followed by a `where`
+    // clause, `cfg` attributes also apply to the code generated for the variant while the other
+    // attributes only apply to the variant itself, except for the options of the macro such as
+    // `#[no_from]`, `#[assert_unwind_safe]`, `#[hot]` or `#[cold]`, an `err:` closure following a
+    // variant converts its errors and a variant marked as `(infallible)` never fails
+    (@variants $ctx:tt $params:tt $w:tt $v:tt [$($c:tt)*] $a:tt # [cfg $args:tt] $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params $w $v [$($c)* #[cfg $args]] $a $($rest)*);
+    );
+    (@variants $ctx:tt $params:tt $w:tt $v:tt $c:tt [$a:tt [$($o:tt)*]] # [no_from]
+     $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params $w $v $c [$a [$($o)* no_from]] $($rest)*);
+    );
+    (@variants $ctx:tt $params:tt $w:tt $v:tt $c:tt [$a:tt [$($o:tt)*]] # [hot] $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params $w $v $c [$a [$($o)* hot]] $($rest)*);
+    );
+    (@variants $ctx:tt $params:tt $w:tt $v:tt $c:tt [$a:tt [$($o:tt)*]] # [cold] $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params $w $v $c [$a [$($o)* cold]] $($rest)*);
+    );
+    (@variants $ctx:tt $params:tt $w:tt $v:tt $c:tt [$a:tt [$($o:tt)*]] # [assert_unwind_safe]
+     $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params $w $v $c [$a [$($o)* assert_unwind_safe]]
+                               $($rest)*);
+    );
+    (@variants $ctx:tt $params:tt $w:tt $v:tt $c:tt [[$($a:tt)*] $o:tt] # [$($attr:tt)*]
+     $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params $w $v $c [[$($a)* #[$($attr)*]] $o]
+                               $($rest)*);
+    );
+    (@variants $ctx:tt $params:tt $w:tt $v:tt $c:tt [$a:tt [$($o:tt)*]]
+     $n:ident (infallible) => $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params $w $v $c [$a [$($o)* infallible]]
+                               $n => $($rest)*);
+    );
+    (@variants $ctx:tt $params:tt $w:tt [$($v:tt)*] $c:tt [$a:tt [$($o:tt)*]]
+     $n:ident => $ft:ty , err : $f:expr , $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params $w
+                               [$($v)* { $c [$a [$($o)* err ($f)]] $n $ft }] [] [[] []]
+                               $($rest)*);
+    );
+    (@variants $ctx:tt $params:tt $w:tt [$($v:tt)*] $c:tt [$a:tt [$($o:tt)*]]
+     $n:ident => $ft:ty , err : $f:expr) => (
+        $crate::__union_parse!(@hot $ctx $params $w [$($v)* { $c [$a [$($o)* err ($f)]] $n $ft }]);
+    );
+    (@variants $ctx:tt $params:tt $w:tt [$($v:tt)*] $c:tt $a:tt $n:ident => $ft:ty ,
+     $($rest:tt)*) => (
+        $crate::__union_parse!(@variants $ctx $params $w [$($v)* { $c $a $n $ft }] [] [[] []]
+                               $($rest)*);
+    );
+    (@variants $ctx:tt $params:tt $w:tt [$($v:tt)*] $c:tt $a:tt $n:ident => $ft:ty where
+     $($rest:tt)*) => (
+        $crate::__union_parse!(@where tail $ctx $params $w [$($v)* { $c $a $n $ft }] + $($rest)*);
+    );
+    (@variants $ctx:tt $params:tt $w:tt [$($v:tt)*] $c:tt $a:tt $n:ident => $ft:ty) => (
+        $crate::__union_parse!(@hot $ctx $params $w [$($v)* { $c $a $n $ft }]);
+    );
+    // the end of the variants, after a trailing comma
+    (@variants $ctx:tt $params:tt $w:tt $v:tt [] [[] []]) => (
+        $crate::__union_parse!(@hot $ctx $params $w $v);
+    );
+
+    // when a variant is marked as `#[hot]`, every variant gets a `hot_sibling` option after its
+    // other options, so the variants which are not hot are treated as cold
+    (@hot $ctx:tt $params:tt $w:tt $v:tt) => (
+        $crate::__union_parse!(@hot_scan [$ctx $params $w $v] $v);
+    );
+    (@hot_scan $args:tt [{ $c:tt [$a:tt [hot $($o:tt)*]] $n:ident $ft:ty } $($v:tt)*]) => (
+        $crate::__union_parse!(@hot_mark $args);
+    );
+    (@hot_scan $args:tt [{ $c:tt [$a:tt [$o:tt $($os:tt)*]] $n:ident $ft:ty } $($v:tt)*]) => (
+        $crate::__union_parse!(@hot_scan $args [{ $c [$a [$($os)*]] $n $ft } $($v)*]);
+    );
+    (@hot_scan $args:tt [{ $c:tt [$a:tt []] $n:ident $ft:ty } $($v:tt)*]) => (
+        $crate::__union_parse!(@hot_scan $args [$($v)*]);
+    );
+    (@hot_scan [$ctx:tt $params:tt $w:tt $v:tt] []) => (
+        $crate::__union_parse!(@done $ctx $params $w $v);
+    );
+    (@hot_mark [$ctx:tt $params:tt $w:tt
+                [$({ $c:tt [$a:tt [$($o:tt)*]] $n:ident $ft:ty })*]]) => (
+        $crate::__union_parse!(@done $ctx $params $w [$({ $c [$a [$($o)* hot_sibling]] $n $ft })*]);
+    );
+
+    (@done [$cb:ident $attrs:tt $vis:tt $name:ident [$($lt:lifetime)*] [] $hdr:tt] [$($p:ident)*]
+     $w:tt $variants:tt) => (
+        $crate::$cb! {
+            $attrs $vis $name [$($lt,)* $($p,)*] [$($lt,)* $($p,)*] $w $hdr $variants
+        }
+    );
+    (@done [$cb:ident $attrs:tt $vis:tt $name:ident [$($lt:lifetime)*] [$c:ident] $hdr:tt]
+     [$($p:ident)*] $w:tt $variants:tt) => (
+        $crate::$cb! {
+            $attrs $vis $name [$($lt,)* const $c: usize, $($p,)*] [$($lt,)* $c, $($p,)*] $w $hdr
+            $variants
+        }
+        $crate::__union_const_count! {
+            $name [$($lt,)* const $c: usize, $($p,)*] [$($lt,)* $c, $($p,)*] $w $c
+        }
+    );
+}
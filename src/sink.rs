@@ -0,0 +1,182 @@
+// Borrows the sink `$s` of the variant `$n` through a function named after the variant, like
+// `__union_poll`, so that a variant which isn't a sink points at the variant of the invocation.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_as_sink {
+    ($n:ident $s:ident) => ({
+        #[allow(non_snake_case)]
+        #[inline(always)]
+        fn $n<S>(s: &mut S) -> &mut S
+            where S: $crate::__futures::Sink + ?Sized
+        {
+            s
+        }
+        $n($s)
+    });
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_sink {
+    ($attrs:tt $vis:tt $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] [$item:ty, $err:ty]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        $crate::__union_enum!($attrs $vis $name [$($g)*] [$($u)*] [$($w)*]
+                              [$({ [$($cfg)*] $va $n $ft })*]);
+
+        #[allow(deprecated, unused_variables)]
+        impl<$($g)*> $crate::__futures::Sink for $name<$($u)*> where $($w)* {
+            type SinkItem = $item;
+            type SinkError = $err;
+
+            fn start_send(&mut self, item: Self::SinkItem)
+                -> $crate::__futures::StartSend<Self::SinkItem, Self::SinkError> {
+                match *self {
+                    $(
+                        $($cfg)* $name::$n(ref mut s) => {
+                            let s = $crate::__union_as_sink!($n s);
+                            s.start_send(item).map_err(|e| $crate::__union_err!($va $n e))
+                        }
+                        ),*
+                }
+            }
+
+            fn poll_complete(&mut self) -> $crate::__futures::Poll<(), Self::SinkError> {
+                match *self {
+                    $(
+                        $($cfg)* $name::$n(ref mut s) => {
+                            let s = $crate::__union_as_sink!($n s);
+                            s.poll_complete().map_err(|e| $crate::__union_err!($va $n e))
+                        }
+                        ),*
+                }
+            }
+
+            fn close(&mut self) -> $crate::__futures::Poll<(), Self::SinkError> {
+                match *self {
+                    $(
+                        $($cfg)* $name::$n(ref mut s) => {
+                            let s = $crate::__union_as_sink!($n s);
+                            s.close().map_err(|e| $crate::__union_err!($va $n e))
+                        }
+                        ),*
+                }
+            }
+        }
+    );
+}
+
+/// A macro to create a sink that has branched from multiple underlying sinks of distinct
+/// types.
+///
+/// The enum implements `Sink` by delegating `start_send`, `poll_complete` and `close` to the
+/// active variant. Every underlying sink must accept the same `SinkItem`, since an item that is
+/// not accepted is handed back to the caller, but sink errors are converted with the `From`
+/// trait.
+///
+/// ```
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// use futures::sync::mpsc::{SendError, UnboundedSender};
+///
+/// pub enum SinkError {
+///     Memory,
+///     Disconnected,
+/// }
+///
+/// impl From<()> for SinkError {
+///     fn from(_: ()) -> SinkError {
+///         SinkError::Memory
+///     }
+/// }
+///
+/// impl From<SendError<u64>> for SinkError {
+///     fn from(_: SendError<u64>) -> SinkError {
+///         SinkError::Disconnected
+///     }
+/// }
+///
+/// union_sink!(NumberSink<u64, SinkError>,
+///       Memory => Vec<u64>,
+///       Channel => UnboundedSender<u64>);
+/// # fn main() {}
+/// ```
+///
+/// A variant which isn't a sink is reported at the variant:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// union_sink!(NumberSink<u64, ()>,
+///       Memory => Vec<u64>,
+///       // error: the trait `futures::Sink` is not implemented for `String`
+///       Other => String);
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! union_sink {
+    ($($args:tt)*) => (
+        $crate::__union_parse!(@start __union_sink $($args)*);
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{Async, AsyncSink, Future, Sink, Stream};
+    use futures::sync::mpsc::{unbounded, SendError, UnboundedSender};
+
+    #[derive(PartialEq, Debug, Eq)]
+    pub enum Error {
+        Memory,
+        Disconnected,
+    }
+
+    impl From<()> for Error {
+        fn from(_: ()) -> Error {
+            Error::Memory
+        }
+    }
+
+    impl From<SendError<u64>> for Error {
+        fn from(_: SendError<u64>) -> Error {
+            Error::Disconnected
+        }
+    }
+
+    union_sink!(TestSink<u64, Error>,
+            Memory => Vec<u64>,
+            Channel => UnboundedSender<u64>);
+
+    #[test]
+    fn memory_sink() {
+        let mut a: TestSink = Vec::new().into();
+        assert_eq!(Ok(AsyncSink::Ready), a.start_send(5));
+        assert_eq!(Ok(Async::Ready(())), a.poll_complete());
+        assert_eq!(Ok(Async::Ready(())), a.close());
+        match a {
+            TestSink::Memory(v) => assert_eq!(vec![5u64], v),
+            TestSink::Channel(_) => panic!("expected memory sink"),
+        }
+    }
+
+    #[test]
+    fn channel_sink() {
+        let (tx, rx) = unbounded();
+        let mut a: TestSink = tx.into();
+        assert_eq!(Ok(AsyncSink::Ready), a.start_send(5));
+        assert_eq!(Ok(Async::Ready(())), a.poll_complete());
+        drop(a);
+        assert_eq!(Ok(vec![5u64]), rx.collect().wait());
+    }
+
+    #[test]
+    fn error_conversion() {
+        let (tx, rx) = unbounded();
+        drop(rx);
+        let mut a: TestSink = tx.into();
+        assert_eq!(Err(Error::Disconnected), a.start_send(5));
+    }
+}
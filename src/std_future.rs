@@ -0,0 +1,641 @@
+// Polls the pinned future `$f` of the variant `$n` through a function named after the variant,
+// like `__union_poll`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_poll_std {
+    ($n:ident ($f:expr) $cx:ident) => ({
+        #[allow(non_snake_case)]
+        #[inline(always)]
+        #[track_caller]
+        fn $n<F, T>(f: ::std::pin::Pin<&mut F>, cx: &mut ::std::task::Context<'_>)
+            -> ::std::task::Poll<T>
+            where F: ::std::future::Future<Output = T> + ?Sized
+        {
+            f.poll(cx)
+        }
+        $n($f, $cx)
+    });
+}
+
+// The `FusedFuture` impl of `futures-core` delegating to the active variant, bounded by the
+// variants like the impls of `__union_traits`.
+#[cfg(feature = "futures-core")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_fused {
+    ($name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        const _: () = {
+            $crate::__union_traits!(@variants $name [$($g)*] [$($u)*] [$($w)*]
+                                    [$({ [$($cfg)*] $va $n $ft })*]);
+
+            #[allow(deprecated)]
+            impl<$($g)*> $crate::__futures_core::future::FusedFuture for $name<$($u)*>
+                where $($w)*
+                      $(
+                          for<'__union> <Self as __UnionVariants>::$n:
+                              $crate::__futures_core::future::FusedFuture,
+                      )*
+            {
+                fn is_terminated(&self) -> bool {
+                    match *self {
+                        $(
+                            $($cfg)*
+                            $name::$n(ref f) => {
+                                $crate::__futures_core::future::FusedFuture::is_terminated(f)
+                            }
+                        )*
+                    }
+                }
+            }
+        };
+    );
+}
+
+#[cfg(not(feature = "futures-core"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_fused {
+    ($name:ident $g:tt $u:tt $w:tt $v:tt) => ();
+}
+
+// The conversions into the `FutureObj` and `LocalFutureObj` of `futures-task`, which box the
+// enum. The `Send` bound is higher-ranked, so it isn't rejected as trivially false for an enum
+// which is not `Send`.
+#[cfg(feature = "futures-task")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_future_obj {
+    ($name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] [$output:ty]) => (
+        #[allow(deprecated)]
+        impl<'__union, $($g)*> ::std::convert::From<$name<$($u)*>>
+            for $crate::__futures_task::FutureObj<'__union, $output>
+            where $($w)*
+                  $name<$($u)*>: '__union,
+                  for<'__send> $name<$($u)*>: ::std::marker::Send
+        {
+            fn from(other: $name<$($u)*>) -> Self {
+                $crate::__futures_task::FutureObj::new(::std::boxed::Box::new(other))
+            }
+        }
+
+        #[allow(deprecated)]
+        impl<'__union, $($g)*> ::std::convert::From<$name<$($u)*>>
+            for $crate::__futures_task::LocalFutureObj<'__union, $output>
+            where $($w)*
+                  $name<$($u)*>: '__union
+        {
+            fn from(other: $name<$($u)*>) -> Self {
+                $crate::__futures_task::LocalFutureObj::new(::std::boxed::Box::new(other))
+            }
+        }
+    );
+}
+
+#[cfg(not(feature = "futures-task"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_future_obj {
+    ($name:ident $g:tt $u:tt $w:tt $output:tt) => ();
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_future_03 {
+    (@poll [$($inline:tt)*] $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] [$item:ty, $err:ty]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        // the context is unused and the end of `poll` is unreachable when the enum has no variants
+        #[allow(deprecated, unused_variables)]
+        impl<$($g)*> ::std::future::Future for $name<$($u)*> where $($w)* {
+            type Output = ::std::result::Result<$item, $err>;
+
+            #[track_caller]
+            #[allow(unreachable_code)]
+            $($inline)*
+            fn poll(self: ::std::pin::Pin<&mut Self>, cx: &mut ::std::task::Context<'_>)
+                -> ::std::task::Poll<Self::Output> {
+                // the variants are never moved out of the pinned enum
+                let this = unsafe { self.get_unchecked_mut() };
+                let poll = match *this {
+                    $(
+                        $($cfg)* $name::$n(ref mut f) => {
+                            $crate::__union_cold!($va);
+                            // the variant is pinned like the enum
+                            let f = unsafe { ::std::pin::Pin::new_unchecked(f) };
+                            match $crate::__union_poll_std!($n (f) cx) {
+                                ::std::task::Poll::Ready(::std::result::Result::Ok(t)) => {
+                                    ::std::result::Result::Ok($crate::__union_from!($n t))
+                                }
+                                #[allow(unreachable_code)]
+                                ::std::task::Poll::Ready(::std::result::Result::Err(e)) => {
+                                    ::std::result::Result::Err($crate::__union_err!($va $n e))
+                                }
+                                ::std::task::Poll::Pending => return ::std::task::Poll::Pending,
+                            }
+                        }
+                        ),*
+                };
+                ::std::task::Poll::Ready(poll)
+            }
+        }
+    );
+    ([$($attrs:tt)*] $vis:tt $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] [$item:ty, $err:ty]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        $crate::__union_enum!([#[must_use = "futures do nothing unless polled"] $($attrs)*] $vis
+                              $name [$($g)*] [$($u)*] [$($w)*]
+                              [$({ [$($cfg)*] $va $n $ft })*]);
+        $crate::__union_pin_drop_guard!($name [$($g)*] [$($u)*] [$($w)*]);
+        $crate::__union_unpin!($name [$($g)*] [$($u)*] [$($w)*] [$({ [$($cfg)*] $va $n $ft })*]);
+        $crate::__union_fused!($name [$($g)*] [$($u)*] [$($w)*] [$({ [$($cfg)*] $va $n $ft })*]);
+        $crate::__union_future_obj!($name [$($g)*] [$($u)*] [$($w)*]
+                                    [::std::result::Result<$item, $err>]);
+        $crate::__union_inline!([$($attrs)*] __union_future_03
+                                $name [$($g)*] [$($u)*] [$($w)*] [$item, $err]
+                                [$({ [$($cfg)*] $va $n $ft })*]);
+    );
+}
+
+/// A macro to create a futures 0.3 future that has branched from multiple underlying futures of
+/// distinct types.
+///
+/// This is the counterpart of `union_future` for the `Pin` based API of futures 0.3, which is
+/// the `std::future::Future` trait. The underlying futures resolve to a `Result` whose value and
+/// error are converted with the `From` trait, so the generated enum implements
+/// `Future<Output = Result<Item, Error>>`. The pin is projected onto the active variant, so the
+/// underlying futures do not need to be `Unpin`. For that reason the generated enum must not
+/// implement `Drop`, nor `Unpin`. The enum is `Unpin` when all of the underlying futures are, so
+/// it can be polled through `Pin::new(&mut future)` without boxing it.
+///
+/// Like any future, the enum implements `IntoFuture` through the blanket impl of the standard
+/// library, so it can be passed where an `IntoFuture` is expected and awaited directly. Since the
+/// variants are all of one type, a `Vec` of the enum can be joined with `join_all` or
+/// `try_join_all` of futures 0.3, which pin the futures themselves, so the variants need not be
+/// `Unpin`.
+///
+/// ```
+/// #[macro_use]
+/// extern crate union_future;
+///
+/// use std::future::{ready, Ready};
+///
+/// # pub struct DbError;
+/// # pub type DbQueryFuture<T> = std::future::Pending<Result<T, DbError>>;
+/// union_future_03!(QueryFuture<u64, DbError>,
+///       Cached => Ready<Result<u64, DbError>>,
+///       Db => DbQueryFuture<u64>);
+///
+/// fn cached(val: u64) -> QueryFuture {
+///     ready(Ok(val)).into()
+/// }
+/// # fn main() {}
+/// ```
+///
+/// Implementing `Unpin` for the enum would allow moving a variant which is not `Unpin` out of the
+/// pinned enum, so it conflicts with the impl generated by the macro:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate union_future;
+///
+/// use std::future::{ready, Ready};
+///
+/// # pub struct Unmovable(std::marker::PhantomPinned);
+/// # impl std::future::Future for Unmovable {
+/// #     type Output = Result<u64, ()>;
+/// #     fn poll(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>)
+/// #         -> std::task::Poll<Result<u64, ()>> {
+/// #         std::task::Poll::Pending
+/// #     }
+/// # }
+/// union_future_03!(QueryFuture<u64, ()>,
+///       Cached => Ready<Result<u64, ()>>,
+///       Pinned => Unmovable);
+///
+/// impl Unpin for QueryFuture {}
+/// # fn main() {}
+/// ```
+///
+/// The `err:` closure of a variant is not expanded within the `unsafe` code projecting the pin,
+/// so it cannot use unsafe operations without an `unsafe` block of its own:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate union_future;
+///
+/// use std::future::Ready;
+///
+/// union_future_03!(QueryFuture<u64, u64>,
+///       Remote => Ready<Result<u64, String>>, err: |_: String| {
+///           let p = &5u64 as *const u64;
+///           *p
+///       });
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! union_future_03 {
+    ($($args:tt)*) => (
+        $crate::__union_parse!(@start __union_future_03 $($args)*);
+    );
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_future_std {
+    (@poll [$($inline:tt)*] $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] [$output:ty]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        #[allow(deprecated, unused_variables)]
+        impl<$($g)*> ::std::future::Future for $name<$($u)*> where $($w)* {
+            type Output = $output;
+
+            #[track_caller]
+            $($inline)*
+            fn poll(self: ::std::pin::Pin<&mut Self>, cx: &mut ::std::task::Context<'_>)
+                -> ::std::task::Poll<Self::Output> {
+                // the variants are never moved out of the pinned enum
+                let this = unsafe { self.get_unchecked_mut() };
+                match *this {
+                    $(
+                        $($cfg)* $name::$n(ref mut f) => {
+                            $crate::__union_cold!($va);
+                            // the variant is pinned like the enum
+                            let f = unsafe { ::std::pin::Pin::new_unchecked(f) };
+                            match $crate::__union_poll_std!($n (f) cx) {
+                                ::std::task::Poll::Ready(t) => {
+                                    ::std::task::Poll::Ready($crate::__union_from!($n t))
+                                }
+                                ::std::task::Poll::Pending => ::std::task::Poll::Pending,
+                            }
+                        }
+                        ),*
+                }
+            }
+        }
+    );
+    ([$($attrs:tt)*] $vis:tt $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] [$output:ty]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        $crate::__union_enum!([#[must_use = "futures do nothing unless polled"] $($attrs)*] $vis
+                              $name [$($g)*] [$($u)*] [$($w)*]
+                              [$({ [$($cfg)*] $va $n $ft })*]);
+        $crate::__union_pin_drop_guard!($name [$($g)*] [$($u)*] [$($w)*]);
+        $crate::__union_unpin!($name [$($g)*] [$($u)*] [$($w)*] [$({ [$($cfg)*] $va $n $ft })*]);
+        $crate::__union_fused!($name [$($g)*] [$($u)*] [$($w)*] [$({ [$($cfg)*] $va $n $ft })*]);
+        $crate::__union_future_obj!($name [$($g)*] [$($u)*] [$($w)*] [$output]);
+        $crate::__union_inline!([$($attrs)*] __union_future_std
+                                $name [$($g)*] [$($u)*] [$($w)*] [$output]
+                                [$({ [$($cfg)*] $va $n $ft })*]);
+    );
+}
+
+/// A macro to create a `std::future::Future` that has branched from multiple underlying futures
+/// of distinct types.
+///
+/// Unlike `union_future_03`, the underlying futures are not required to resolve to a `Result`.
+/// The output of the active variant is converted to the output of the generated enum with the
+/// `From` trait. Only the standard library is used by the generated code. Like `union_future_03`,
+/// the pin is projected onto the active variant, so the generated enum must implement neither
+/// `Drop` nor `Unpin`, and it is `Unpin` when all of the underlying futures are.
+///
+/// ```
+/// #[macro_use]
+/// extern crate union_future;
+///
+/// use std::future::{ready, Ready};
+///
+/// # pub type Lookup = std::future::Pending<u32>;
+/// union_future_std!(ValueFuture<u64>,
+///       Cached => Ready<u64>,
+///       Lookup => Lookup);
+///
+/// fn cached(val: u64) -> ValueFuture {
+///     ready(val).into()
+/// }
+/// # fn main() {}
+/// ```
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate union_future;
+///
+/// use std::future::{Pending, Ready};
+/// use std::marker::PhantomPinned;
+///
+/// # pub struct Unmovable(Pending<u64>, PhantomPinned);
+/// # impl std::future::Future for Unmovable {
+/// #     type Output = u64;
+/// #     fn poll(self: std::pin::Pin<&mut Self>, _: &mut std::task::Context<'_>)
+/// #         -> std::task::Poll<u64> {
+/// #         std::task::Poll::Pending
+/// #     }
+/// # }
+/// union_future_std!(ValueFuture<u64>,
+///       Cached => Ready<u64>,
+///       Pinned => Unmovable);
+///
+/// impl Unpin for ValueFuture {}
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! union_future_std {
+    ($($args:tt)*) => (
+        $crate::__union_parse!(@start __union_future_std $($args)*);
+    );
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use std::future::{pending, ready, Future, Pending, Ready};
+    use std::marker::PhantomPinned;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, Waker};
+
+    #[derive(PartialEq, Debug, Eq)]
+    pub enum Error {
+        Fail,
+        BigFail,
+    }
+
+    #[derive(PartialEq, Debug, Eq)]
+    pub struct OtherError {
+        op: u64
+    }
+
+    impl From<OtherError> for Error {
+        fn from(_: OtherError) -> Error {
+            Error::BigFail
+        }
+    }
+
+    pub struct PinnedFuture {
+        val: Option<Result<u32, OtherError>>,
+        _pin: PhantomPinned,
+    }
+
+    impl Future for PinnedFuture {
+        type Output = Result<u32, OtherError>;
+
+        fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Self::Output> {
+            let val = unsafe { &mut self.get_unchecked_mut().val };
+            Poll::Ready(val.take().expect("polled after completion"))
+        }
+    }
+
+    fn poll<F: Future>(f: F) -> Poll<F::Output> {
+        let mut cx = Context::from_waker(Waker::noop());
+        Box::pin(f).as_mut().poll(&mut cx)
+    }
+
+    #[test]
+    fn same_types() {
+        union_future_03!(TestFut<u64, Error>,
+                Forever => Pending<Result<u64, Error>>,
+                Immediate => Ready<Result<u64, Error>>);
+
+        let a: TestFut = pending::<Result<u64, Error>>().into();
+        assert_eq!(Poll::Pending, poll(a));
+        let b: TestFut = ready(Ok::<u64, Error>(5)).into();
+        assert_eq!(Poll::Ready(Ok(5u64)), poll(b));
+    }
+
+    #[test]
+    fn different_types() {
+        union_future_03!(TestFut<f64, Error>,
+                Number => Ready<Result<f32, Error>>,
+                Pinned => PinnedFuture);
+
+        let a: TestFut = ready(Ok::<f32, Error>(5.25)).into();
+        assert_eq!(Poll::Ready(Ok(5.25f64)), poll(a));
+        let b: TestFut = PinnedFuture { val: Some(Ok(5)), _pin: PhantomPinned }.into();
+        assert_eq!(Poll::Ready(Ok(5f64)), poll(b));
+        let c: TestFut = PinnedFuture { val: Some(Err(OtherError { op: 1 })), _pin: PhantomPinned }.into();
+        assert_eq!(Poll::Ready(Err(Error::BigFail)), poll(c));
+    }
+
+    #[test]
+    fn unpin() {
+        fn is_unpin<T: Unpin>(_: &T) {}
+
+        union_future_03!(TestFut<u64, Error>,
+                Forever => Pending<Result<u64, Error>>,
+                Immediate => Ready<Result<u64, Error>>);
+
+        union_future_std!(StdFut<u64>,
+                Forever => Pending<u64>,
+                Immediate => Ready<u64>);
+
+        let mut a: TestFut = ready(Ok::<u64, Error>(5)).into();
+        is_unpin(&a);
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(Poll::Ready(Ok(5u64)), Pin::new(&mut a).poll(&mut cx));
+        let mut b: StdFut = ready(5u64).into();
+        is_unpin(&b);
+        assert_eq!(Poll::Ready(5u64), Pin::new(&mut b).poll(&mut cx));
+    }
+
+    #[test]
+    fn generic_types() {
+        union_future_03!(TestFut<T, Error> where T: Unpin,
+                Forever => Pending<Result<T, Error>>,
+                Immediate => Ready<Result<T, Error>>);
+
+        let a: TestFut<u64> = ready(Ok::<u64, Error>(5)).into();
+        assert_eq!(Poll::Ready(Ok(5u64)), poll(a));
+        let b: TestFut<String> = pending::<Result<String, Error>>().into();
+        assert_eq!(Poll::Pending, poll(b));
+    }
+
+    #[test]
+    fn std_output() {
+        union_future_std!(TestFut<f64>,
+                Forever => Pending<f64>,
+                Number => Ready<u32>,
+                Floating => Ready<f32>);
+
+        let a: TestFut = pending::<f64>().into();
+        assert_eq!(Poll::Pending, poll(a));
+        let b: TestFut = ready(5u32).into();
+        assert_eq!(Poll::Ready(5f64), poll(b));
+        let c: TestFut = ready(5.25f32).into();
+        assert_eq!(Poll::Ready(5.25f64), poll(c));
+    }
+
+    #[test]
+    fn waker() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use std::task::Wake;
+
+        struct CountingWaker(AtomicUsize);
+
+        impl Wake for CountingWaker {
+            fn wake(self: Arc<Self>) {
+                self.wake_by_ref();
+            }
+
+            fn wake_by_ref(self: &Arc<Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        // pending on the first poll, after waking the task so it is polled again
+        pub struct YieldOnce<T>(Option<T>, bool);
+
+        impl<T: Unpin> Future for YieldOnce<T> {
+            type Output = Result<T, Error>;
+
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+                if self.1 {
+                    Poll::Ready(Ok(self.0.take().expect("polled after completion")))
+                } else {
+                    self.1 = true;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }
+        }
+
+        union_future_03!(TestFut<u64, Error>,
+                Number => YieldOnce<u32>,
+                Big => YieldOnce<u64>);
+
+        let counter = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+        let futures: Vec<TestFut> = vec![YieldOnce(Some(5u32), false).into(),
+                                         YieldOnce(Some(5u64), false).into()];
+        for (i, mut f) in futures.into_iter().enumerate() {
+            assert_eq!(Poll::Pending, Pin::new(&mut f).poll(&mut cx));
+            assert_eq!(i + 1, counter.0.load(Ordering::SeqCst));
+            assert_eq!(Poll::Ready(Ok(5u64)), Pin::new(&mut f).poll(&mut cx));
+            assert_eq!(i + 1, counter.0.load(Ordering::SeqCst));
+        }
+    }
+
+    #[cfg(all(feature = "futures-03", not(feature = "futures-01")))]
+    #[test]
+    fn union_future_selects_futures_03() {
+        union_future!(TestFut<u64, Error>,
+                Forever => Pending<Result<u64, Error>>,
+                Immediate => Ready<Result<u64, Error>>);
+
+        let a: TestFut = ready(Ok::<u64, Error>(5)).into();
+        assert_eq!(Poll::Ready(Ok(5u64)), poll(a));
+    }
+
+    #[test]
+    fn zero_variants() {
+        union_future_03!(Never<u64, Error>);
+        union_future_std!(NeverStd<u64>);
+
+        fn assert_future<F: Future>() {}
+        assert_future::<Never>();
+        assert_future::<NeverStd>();
+    }
+
+    #[cfg(all(feature = "futures-03", not(feature = "futures-01")))]
+    #[test]
+    fn impl_only() {
+        pub enum TestFut {
+            Forever(Pending<Result<u64, Error>>),
+            Immediate(Ready<Result<u64, Error>>),
+        }
+
+        union_future_impl!(TestFut<u64, Error>,
+                Forever => Pending<Result<u64, Error>>,
+                Immediate => Ready<Result<u64, Error>>);
+
+        let a: TestFut = pending::<Result<u64, Error>>().into();
+        assert_eq!(Poll::Pending, poll(a));
+        let b: TestFut = ready(Ok::<u64, Error>(5)).into();
+        assert_eq!(Poll::Ready(Ok(5u64)), poll(b));
+    }
+
+    #[cfg(feature = "futures-core")]
+    #[test]
+    fn fused() {
+        use __futures_core::future::FusedFuture;
+
+        // resolves once, and is terminated afterwards
+        pub struct Fused(Option<u64>);
+
+        impl Future for Fused {
+            type Output = Result<u64, Error>;
+
+            fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Self::Output> {
+                Poll::Ready(Ok(self.0.take().expect("polled after completion")))
+            }
+        }
+
+        impl FusedFuture for Fused {
+            fn is_terminated(&self) -> bool {
+                self.0.is_none()
+            }
+        }
+
+        union_future_03!(TestFut<u64, Error>,
+                Immediate => Fused);
+
+        let mut a: TestFut = Fused(Some(5)).into();
+        assert!(!a.is_terminated());
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(Poll::Ready(Ok(5u64)), Pin::new(&mut a).poll(&mut cx));
+        assert!(a.is_terminated());
+    }
+
+    #[cfg(feature = "futures-core")]
+    #[test]
+    fn try_future() {
+        use __futures_core::future::TryFuture;
+
+        fn try_poll<F: TryFuture + Unpin>(mut f: F) -> Poll<Result<F::Ok, F::Error>> {
+            let mut cx = Context::from_waker(Waker::noop());
+            Pin::new(&mut f).try_poll(&mut cx)
+        }
+
+        union_future_03!(TestFut<u64, Error>,
+                Forever => Pending<Result<u64, Error>>,
+                Immediate => Ready<Result<u64, Error>>);
+
+        let a: TestFut = ready(Err::<u64, Error>(Error::Fail)).into();
+        assert_eq!(Poll::Ready(Err(Error::Fail)), try_poll(a));
+        let b: TestFut = pending::<Result<u64, Error>>().into();
+        assert_eq!(Poll::Pending, try_poll(b));
+    }
+
+    #[cfg(feature = "futures-task")]
+    #[test]
+    fn future_obj() {
+        use std::rc::Rc;
+        use __futures_task::{FutureObj, LocalFutureObj};
+
+        union_future_03!(TestFut<u64, Error>,
+                Forever => Pending<Result<u64, Error>>,
+                Immediate => Ready<Result<u64, Error>>);
+        union_future_std!(LocalFut<Rc<u64>>,
+                Immediate => Ready<Rc<u64>>);
+
+        let a: TestFut = ready(Ok::<u64, Error>(5)).into();
+        let obj: FutureObj<'static, Result<u64, Error>> = a.into();
+        assert_eq!(Poll::Ready(Ok(5u64)), poll(obj));
+
+        let b: LocalFut = ready(Rc::new(6)).into();
+        let obj: LocalFutureObj<'static, Rc<u64>> = b.into();
+        assert_eq!(Poll::Ready(Rc::new(6)), poll(obj));
+    }
+
+    #[test]
+    fn into_future() {
+        use std::future::IntoFuture;
+
+        fn into_future<F: IntoFuture<Output = Result<u64, Error>>>(f: F) -> F::IntoFuture {
+            f.into_future()
+        }
+
+        union_future_03!(TestFut<u64, Error>,
+                Forever => Pending<Result<u64, Error>>,
+                Immediate => Ready<Result<u64, Error>>);
+
+        let a: TestFut = ready(Ok::<u64, Error>(5)).into();
+        assert_eq!(Poll::Ready(Ok(5u64)), poll(into_future(a)));
+    }
+}
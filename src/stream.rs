@@ -0,0 +1,255 @@
+// Polls the stream `$s` of the variant `$n` through a function named after the variant, like
+// `__union_poll`, so that a variant which isn't a stream points at the variant of the invocation.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_poll_stream {
+    ($n:ident $s:ident) => ({
+        #[allow(non_snake_case)]
+        #[inline(always)]
+        #[track_caller]
+        fn $n<S, T, E>(s: &mut S) -> $crate::__futures::Poll<::std::option::Option<T>, E>
+            where S: $crate::__futures::Stream<Item = T, Error = E> + ?Sized
+        {
+            s.poll()
+        }
+        $n($s)
+    });
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_stream {
+    (@poll [$($inline:tt)*] $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] [$item:ty, $err:ty]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        #[allow(deprecated)]
+        impl<$($g)*> $crate::__futures::Stream for $name<$($u)*> where $($w)* {
+            type Item = $item;
+            type Error = $err;
+
+            #[track_caller]
+            $($inline)*
+            fn poll(&mut self)
+                -> $crate::__futures::Poll<::std::option::Option<Self::Item>, Self::Error> {
+                match *self {
+                    $(
+                        $($cfg)* $name::$n(ref mut s) => {
+                            $crate::__union_cold!($va);
+                            match $crate::__union_poll_stream!($n s) {
+                                ::std::result::Result::Ok($crate::__futures::Async::Ready(t)) => {
+                                    let t = t.map(|t| $crate::__union_from!($n t));
+                                    ::std::result::Result::Ok($crate::__futures::Async::Ready(t))
+                                }
+                                ::std::result::Result::Ok($crate::__futures::Async::NotReady) => {
+                                    ::std::result::Result::Ok($crate::__futures::Async::NotReady)
+                                }
+                                #[allow(unreachable_code)]
+                                ::std::result::Result::Err(e) => {
+                                    ::std::result::Result::Err($crate::__union_err!($va $n e))
+                                }
+                            }
+                        }
+                        ),*
+                }
+            }
+        }
+    );
+    ([$($attrs:tt)*] $vis:tt $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] [$item:ty, $err:ty]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        $crate::__union_enum!([#[must_use = "streams do nothing unless polled"] $($attrs)*] $vis
+                              $name [$($g)*] [$($u)*] [$($w)*]
+                              [$({ [$($cfg)*] $va $n $ft })*]);
+        $crate::__union_inline!([$($attrs)*] __union_stream
+                                $name [$($g)*] [$($u)*] [$($w)*] [$item, $err]
+                                [$({ [$($cfg)*] $va $n $ft })*]);
+    );
+}
+
+/// A macro to create a stream that has branched from multiple underlying streams of distinct
+/// types.
+///
+/// The syntax and generated code mirror `union_future`: the enum implements `Stream` by
+/// delegating to the active variant, converting items and errors with the `From` trait.
+///
+/// ```
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// use futures::stream::*;
+///
+/// union_stream!(NumberStream<u64, ()>,
+///       Cached => IterOk<std::vec::IntoIter<u64>, ()>,
+///       Nothing => Empty<u32, ()>);
+///
+/// fn numbers(cached: Option<Vec<u64>>) -> NumberStream {
+///     match cached {
+///         Some(ns) => iter_ok(ns).into(),
+///         None => empty().into(),
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
+/// A variant which isn't a stream is reported at the variant:
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// use futures::stream::*;
+///
+/// union_stream!(NumberStream<u64, ()>,
+///       Cached => IterOk<std::vec::IntoIter<u64>, ()>,
+///       // error: the trait `futures::Stream` is not implemented for `String`
+///       Other => String);
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! union_stream {
+    ($($args:tt)*) => (
+        $crate::__union_parse!(@start __union_stream $($args)*);
+    );
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_try_stream {
+    (@poll [$($inline:tt)*] $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] [$item:ty, $err:ty]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        #[allow(deprecated)]
+        impl<$($g)*> $crate::__futures::Stream for $name<$($u)*> where $($w)* {
+            type Item = ::std::result::Result<$item, $err>;
+            type Error = ::std::convert::Infallible;
+
+            #[track_caller]
+            $($inline)*
+            fn poll(&mut self)
+                -> $crate::__futures::Poll<::std::option::Option<Self::Item>, Self::Error> {
+                match *self {
+                    $(
+                        $($cfg)* $name::$n(ref mut s) => {
+                            $crate::__union_cold!($va);
+                            match $crate::__union_poll_stream!($n s) {
+                                ::std::result::Result::Ok($crate::__futures::Async::Ready(t)) => {
+                                    let t = t.map(|t| {
+                                        ::std::result::Result::Ok($crate::__union_from!($n t))
+                                    });
+                                    ::std::result::Result::Ok($crate::__futures::Async::Ready(t))
+                                }
+                                ::std::result::Result::Ok($crate::__futures::Async::NotReady) => {
+                                    ::std::result::Result::Ok($crate::__futures::Async::NotReady)
+                                }
+                                #[allow(unreachable_code)]
+                                ::std::result::Result::Err(e) => {
+                                    let e = $crate::__union_err!($va $n e);
+                                    ::std::result::Result::Ok($crate::__futures::Async::Ready(
+                                        ::std::option::Option::Some(::std::result::Result::Err(e))))
+                                }
+                            }
+                        }
+                        ),*
+                }
+            }
+        }
+    );
+    ([$($attrs:tt)*] $vis:tt $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*] [$item:ty, $err:ty]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        $crate::__union_enum!([#[must_use = "streams do nothing unless polled"] $($attrs)*] $vis
+                              $name [$($g)*] [$($u)*] [$($w)*]
+                              [$({ [$($cfg)*] $va $n $ft })*]);
+        $crate::__union_inline!([$($attrs)*] __union_try_stream
+                                $name [$($g)*] [$($u)*] [$($w)*] [$item, $err]
+                                [$({ [$($cfg)*] $va $n $ft })*]);
+    );
+}
+
+/// A macro to create a stream of `Result` items that has branched from multiple underlying
+/// streams of distinct types.
+///
+/// Rather than ending the stream, an error from the active variant is yielded as an `Err` item,
+/// so the generated enum implements `Stream<Item = Result<T, E>>` and never fails itself. Items
+/// and errors of the underlying streams are converted with the `From` trait.
+///
+/// ```
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// use futures::stream::*;
+///
+/// pub struct DbError;
+///
+/// union_try_stream!(RowStream<u64, DbError>,
+///       Cached => IterOk<std::vec::IntoIter<u64>, DbError>,
+///       Db => IterResult<std::vec::IntoIter<Result<u64, DbError>>>);
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! union_try_stream {
+    ($($args:tt)*) => (
+        $crate::__union_parse!(@start __union_try_stream $($args)*);
+    );
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+    use futures::{Async, Future, Stream};
+    use futures::stream::*;
+    use std::vec;
+
+    #[derive(PartialEq, Debug, Eq)]
+    pub enum Error {
+        Fail,
+        BigFail,
+    }
+
+    #[derive(PartialEq, Debug, Eq)]
+    pub struct OtherError {
+        op: u64
+    }
+
+    impl From<OtherError> for Error {
+        fn from(_: OtherError) -> Error {
+            Error::BigFail
+        }
+    }
+
+    #[test]
+    fn same_types() {
+        union_stream!(TestStream<u64, Error>,
+                Many => IterOk<vec::IntoIter<u64>, Error>,
+                Nothing => Empty<u64, Error>);
+
+        let a: TestStream = iter_ok::<_, Error>(vec![1u64, 2, 3]).into();
+        assert_eq!(Ok(vec![1u64, 2, 3]), a.collect().wait());
+        let mut b: TestStream = empty::<u64, Error>().into();
+        assert_eq!(Ok(Async::Ready(None)), b.poll());
+    }
+
+    #[test]
+    fn different_item_and_err_types() {
+        union_stream!(TestStream<f64, Error>,
+                Number => IterOk<vec::IntoIter<u32>, Error>,
+                Floating => IterResult<vec::IntoIter<Result<f32, OtherError>>>);
+
+        let a: TestStream = iter_ok::<_, Error>(vec![5u32]).into();
+        assert_eq!(Ok(vec![5f64]), a.collect().wait());
+        let mut b: TestStream = iter_result(vec![Ok(5.25f32), Err(OtherError { op: 1 })]).into();
+        assert_eq!(Ok(Async::Ready(Some(5.25f64))), b.poll());
+        assert_eq!(Err(Error::BigFail), b.poll());
+    }
+
+    #[test]
+    fn errors_as_items() {
+        union_try_stream!(TestStream<f64, Error>,
+                Number => IterOk<vec::IntoIter<u32>, Error>,
+                Floating => IterResult<vec::IntoIter<Result<f32, OtherError>>>);
+
+        let a: TestStream = iter_ok::<_, Error>(vec![5u32]).into();
+        assert_eq!(Ok(vec![Ok(5f64)]), a.collect().wait());
+        let b: TestStream = iter_result(vec![Err(OtherError { op: 1 }), Ok(5.25f32)]).into();
+        assert_eq!(Ok(vec![Err(Error::BigFail), Ok(5.25f64)]), b.collect().wait());
+    }
+}
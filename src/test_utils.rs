@@ -0,0 +1,53 @@
+use futures::{executor, Future};
+
+/// Drives the future `fut` to completion on the current thread, returning its result.
+///
+/// This is a shorthand for tests of union futures, since futures 0.1 has no `block_on` function.
+/// The future is spawned on the single-threaded executor of futures 0.1, which blocks the thread
+/// until the future notifies its task, like `Future::wait`. Every union future generated by the
+/// `union_future` macro is a `Future`, so no other bound is required.
+///
+/// ```
+/// #[macro_use]
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// use futures::future::*;
+/// use union_future::block_on_union;
+///
+/// union_future!(QueryFuture<u64, ()>,
+///       Cached => FutureResult<u64, ()>,
+///       Db => Lazy<fn() -> FutureResult<u64, ()>, FutureResult<u64, ()>>);
+///
+/// # fn main() {
+/// let f: QueryFuture = ok(5).into();
+/// assert_eq!(Ok(5), block_on_union(f));
+/// # }
+/// ```
+pub fn block_on_union<F: Future>(fut: F) -> Result<F::Item, F::Error> {
+    executor::spawn(fut).wait_future()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::block_on_union;
+    use futures::future::*;
+
+    fn db() -> FutureResult<u64, ()> {
+        ok(7)
+    }
+
+    union_future!(TestFut<u64, ()>,
+            Cached => FutureResult<u32, ()>,
+            Db => Lazy<fn() -> FutureResult<u64, ()>, FutureResult<u64, ()>>);
+
+    #[test]
+    fn block_on() {
+        let a: TestFut = ok::<u32, ()>(5).into();
+        assert_eq!(Ok(5), block_on_union(a));
+        let b: TestFut = lazy(db as fn() -> FutureResult<u64, ()>).into();
+        assert_eq!(Ok(7), block_on_union(b));
+        let c: TestFut = err::<u32, ()>(()).into();
+        assert_eq!(Err(()), block_on_union(c));
+    }
+}
@@ -0,0 +1,539 @@
+// Implementations of the standard traits which delegate to the active variant.
+//
+// The impls are bounded by the variant types, which are named through the associated types of
+// the private `__UnionVariants` trait so the types of the variants disabled by `cfg` attributes
+// are never named. The bounds are higher-ranked, which keeps them from being rejected as
+// trivially false when the variant types are not generic. Only the traits requested by a
+// `#[union_future(impl(...))]` attribute of the enum are implemented, so the enum can still
+// derive these traits or implement them by hand.
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_traits {
+    (@variants $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     [$({ [$(#[cfg($($p:tt)*)])*] $va:tt $n:ident $ft:ty })*]) => (
+        #[allow(non_camel_case_types)]
+        trait __UnionVariants {
+            $( type $n; )*
+        }
+
+        #[allow(deprecated)]
+        impl<$($g)*> __UnionVariants for $name<$($u)*> where $($w)* {
+            $(
+                $(#[cfg($($p)*)])*
+                type $n = $ft;
+                #[cfg(not(all($($($p)*),*)))]
+                type $n = ();
+            )*
+        }
+    );
+
+    (@clone [Clone $($d:tt)*] $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        // the enum is only `Copy` when all variants are, so `clone` cannot just copy it
+        #[allow(deprecated, clippy::non_canonical_clone_impl)]
+        impl<$($g)*> ::std::clone::Clone for $name<$($u)*>
+            where $($w)*
+                  $( for<'__union> <Self as __UnionVariants>::$n: ::std::clone::Clone, )*
+        {
+            fn clone(&self) -> Self {
+                match *self {
+                    $(
+                        $($cfg)*
+                        $name::$n(ref inner) => $name::$n(::std::clone::Clone::clone(inner)),
+                    )*
+                }
+            }
+        }
+    );
+    (@clone [] $($rest:tt)*) => ();
+    (@clone [$t:tt $($d:tt)*] $($rest:tt)*) => (
+        $crate::__union_traits!(@clone [$($d)*] $($rest)*);
+    );
+
+    (@copy [Copy $($d:tt)*] $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        impl<$($g)*> ::std::marker::Copy for $name<$($u)*>
+            where $($w)*
+                  $( for<'__union> <Self as __UnionVariants>::$n: ::std::marker::Copy, )*
+        {}
+    );
+    (@copy [] $($rest:tt)*) => ();
+    (@copy [$t:tt $($d:tt)*] $($rest:tt)*) => (
+        $crate::__union_traits!(@copy [$($d)*] $($rest)*);
+    );
+
+    (@debug [Debug $($d:tt)*] $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        // the arguments are unused when the enum has no variants
+        #[allow(deprecated, unused_variables)]
+        impl<$($g)*> ::std::fmt::Debug for $name<$($u)*>
+            where $($w)*
+                  $( for<'__union> <Self as __UnionVariants>::$n: ::std::fmt::Debug, )*
+        {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match *self {
+                    $(
+                        $($cfg)*
+                        $name::$n(ref inner) => {
+                            f.debug_tuple(concat!(stringify!($name), "::", stringify!($n)))
+                                .field(inner)
+                                .finish()
+                        }
+                    )*
+                }
+            }
+        }
+    );
+    (@debug [] $($rest:tt)*) => ();
+    (@debug [$t:tt $($d:tt)*] $($rest:tt)*) => (
+        $crate::__union_traits!(@debug [$($d)*] $($rest)*);
+    );
+
+    (@partial_eq [PartialEq $($d:tt)*] $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        #[allow(deprecated, unused_variables)]
+        impl<$($g)*> ::std::cmp::PartialEq for $name<$($u)*>
+            where $($w)*
+                  $( for<'__union> <Self as __UnionVariants>::$n: ::std::cmp::PartialEq, )*
+        {
+            fn eq(&self, other: &Self) -> bool {
+                match (self, other) {
+                    $( $($cfg)* (&$name::$n(ref a), &$name::$n(ref b)) => a == b, )*
+                    #[allow(unreachable_patterns)]
+                    _ => false,
+                }
+            }
+        }
+    );
+    (@partial_eq [] $($rest:tt)*) => ();
+    (@partial_eq [$t:tt $($d:tt)*] $($rest:tt)*) => (
+        $crate::__union_traits!(@partial_eq [$($d)*] $($rest)*);
+    );
+
+    (@eq [Eq $($d:tt)*] $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        impl<$($g)*> ::std::cmp::Eq for $name<$($u)*>
+            where $($w)*
+                  $( for<'__union> <Self as __UnionVariants>::$n: ::std::cmp::Eq, )*
+        {}
+    );
+    (@eq [] $($rest:tt)*) => ();
+    (@eq [$t:tt $($d:tt)*] $($rest:tt)*) => (
+        $crate::__union_traits!(@eq [$($d)*] $($rest)*);
+    );
+
+    // the discriminant is hashed before the inner value, which is consistent with `PartialEq`
+    // since values of different variants are never equal
+    (@hash [Hash $($d:tt)*] $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        #[allow(deprecated, unused_variables)]
+        impl<$($g)*> ::std::hash::Hash for $name<$($u)*>
+            where $($w)*
+                  $( for<'__union> <Self as __UnionVariants>::$n: ::std::hash::Hash, )*
+        {
+            fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+                ::std::hash::Hash::hash(&::std::mem::discriminant(self), state);
+                match *self {
+                    $( $($cfg)* $name::$n(ref inner) => ::std::hash::Hash::hash(inner, state), )*
+                }
+            }
+        }
+    );
+    (@hash [] $($rest:tt)*) => ();
+    (@hash [$t:tt $($d:tt)*] $($rest:tt)*) => (
+        $crate::__union_traits!(@hash [$($d)*] $($rest)*);
+    );
+
+    // variants are ordered by their index, the inner values only when the variants are equal
+    (@partial_ord [PartialOrd $($d:tt)*] $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        // the variants may be `PartialOrd` without being `Ord`, so this cannot call `cmp`
+        #[allow(deprecated, unused_variables, clippy::non_canonical_partial_ord_impl)]
+        impl<$($g)*> ::std::cmp::PartialOrd for $name<$($u)*>
+            where $($w)*
+                  $( for<'__union> <Self as __UnionVariants>::$n: ::std::cmp::PartialOrd, )*
+        {
+            fn partial_cmp(&self, other: &Self) -> ::std::option::Option<::std::cmp::Ordering> {
+                match (self, other) {
+                    $(
+                        $($cfg)*
+                        (&$name::$n(ref a), &$name::$n(ref b)) => {
+                            ::std::cmp::PartialOrd::partial_cmp(a, b)
+                        }
+                    )*
+                    #[allow(unreachable_patterns)]
+                    _ => {
+                        ::std::cmp::PartialOrd::partial_cmp(&self.variant_index(),
+                                                            &other.variant_index())
+                    }
+                }
+            }
+        }
+    );
+    (@partial_ord [] $($rest:tt)*) => ();
+    (@partial_ord [$t:tt $($d:tt)*] $($rest:tt)*) => (
+        $crate::__union_traits!(@partial_ord [$($d)*] $($rest)*);
+    );
+
+    (@ord [Ord $($d:tt)*] $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        #[allow(deprecated, unused_variables)]
+        impl<$($g)*> ::std::cmp::Ord for $name<$($u)*>
+            where $($w)*
+                  $( for<'__union> <Self as __UnionVariants>::$n: ::std::cmp::Ord, )*
+        {
+            fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+                match (self, other) {
+                    $(
+                        $($cfg)*
+                        (&$name::$n(ref a), &$name::$n(ref b)) => ::std::cmp::Ord::cmp(a, b),
+                    )*
+                    #[allow(unreachable_patterns)]
+                    _ => ::std::cmp::Ord::cmp(&self.variant_index(), &other.variant_index()),
+                }
+            }
+        }
+    );
+    (@ord [] $($rest:tt)*) => ();
+    (@ord [$t:tt $($d:tt)*] $($rest:tt)*) => (
+        $crate::__union_traits!(@ord [$($d)*] $($rest)*);
+    );
+
+    (@display [Display $($d:tt)*] $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]
+     [$({ [$($cfg:tt)*] $va:tt $n:ident $ft:ty })*]) => (
+        #[allow(deprecated, unused_variables)]
+        impl<$($g)*> ::std::fmt::Display for $name<$($u)*>
+            where $($w)*
+                  $( for<'__union> <Self as __UnionVariants>::$n: ::std::fmt::Display, )*
+        {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match *self {
+                    $( $($cfg)* $name::$n(ref inner) => ::std::fmt::Display::fmt(inner, f), )*
+                }
+            }
+        }
+    );
+    (@display [] $($rest:tt)*) => ();
+    (@display [$t:tt $($d:tt)*] $($rest:tt)*) => (
+        $crate::__union_traits!(@display [$($d)*] $($rest)*);
+    );
+
+    // the impls of `UnwindSafe` and `RefUnwindSafe` bounded by the variants which are not marked
+    // as `#[assert_unwind_safe]`, which are only emitted when a variant is marked since the auto
+    // impls are the same otherwise
+    (@unwind_safe $ctx:tt $b:tt $any:tt [{ $cfg:tt [$va:tt $o:tt] $n:ident $ft:ty } $($v:tt)*]) => (
+        $crate::__union_traits!(@unwind_safe_options $ctx $b $any $n $o [$($v)*]);
+    );
+    (@unwind_safe_options $ctx:tt $b:tt $any:tt $n:ident [assert_unwind_safe $($o:tt)*]
+     $v:tt) => (
+        $crate::__union_traits!(@unwind_safe $ctx $b + $v);
+    );
+    (@unwind_safe_options $ctx:tt $b:tt $any:tt $n:ident [$o:tt $($os:tt)*] $v:tt) => (
+        $crate::__union_traits!(@unwind_safe_options $ctx $b $any $n [$($os)*] $v);
+    );
+    (@unwind_safe_options $ctx:tt [$($b:tt)*] $any:tt $n:ident [] $v:tt) => (
+        $crate::__union_traits!(@unwind_safe $ctx [$($b)* $n] $any $v);
+    );
+    (@unwind_safe $ctx:tt $b:tt - []) => ();
+    (@unwind_safe [$name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]] [$($n:ident)*] + []) => (
+        #[allow(deprecated)]
+        impl<$($g)*> ::std::panic::UnwindSafe for $name<$($u)*>
+            where $($w)*
+                  $( for<'__union> <Self as __UnionVariants>::$n: ::std::panic::UnwindSafe, )*
+        {
+        }
+
+        #[allow(deprecated)]
+        impl<$($g)*> ::std::panic::RefUnwindSafe for $name<$($u)*>
+            where $($w)*
+                  $( for<'__union> <Self as __UnionVariants>::$n: ::std::panic::RefUnwindSafe, )*
+        {
+        }
+    );
+
+    ($i:tt $name:ident $g:tt $u:tt $w:tt $v:tt) => (
+        const _: () = {
+            $crate::__union_traits!(@variants $name $g $u $w $v);
+            $crate::__union_traits!(@clone $i $name $g $u $w $v);
+            $crate::__union_traits!(@copy $i $name $g $u $w $v);
+            $crate::__union_traits!(@debug $i $name $g $u $w $v);
+            $crate::__union_traits!(@partial_eq $i $name $g $u $w $v);
+            $crate::__union_traits!(@eq $i $name $g $u $w $v);
+            $crate::__union_traits!(@hash $i $name $g $u $w $v);
+            $crate::__union_traits!(@partial_ord $i $name $g $u $w $v);
+            $crate::__union_traits!(@ord $i $name $g $u $w $v);
+            $crate::__union_traits!(@display $i $name $g $u $w $v);
+            $crate::__union_traits!(@unwind_safe [$name $g $u $w] [] - $v);
+        };
+    );
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __union_assert {
+    (assert_send $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]) => (
+        const _: () = {
+            #[allow(dead_code, deprecated)]
+            fn assert_send<$($g)*>() where $($w)* {
+                fn is_send<T: ?Sized + ::std::marker::Send>() {}
+                is_send::<$name<$($u)*>>();
+            }
+        };
+    );
+    (assert_sync $name:ident [$($g:tt)*] [$($u:tt)*] [$($w:tt)*]) => (
+        const _: () = {
+            #[allow(dead_code, deprecated)]
+            fn assert_sync<$($g)*>() where $($w)* {
+                fn is_sync<T: ?Sized + ::std::marker::Sync>() {}
+                is_sync::<$name<$($u)*>>();
+            }
+        };
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use std::future::{pending, ready, Future, Pending, Ready};
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    pub struct Value<T>(T);
+
+    impl<T: fmt::Display> fmt::Display for Value<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "value {}", self.0)
+        }
+    }
+
+    impl<T: Clone + Unpin> Future for Value<T> {
+        type Output = T;
+
+        fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<T> {
+            Poll::Ready(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn clone() {
+        ::union_future_std!(#[union_future(impl(Clone))] TestFut<u64>,
+                Immediate => Ready<u64>,
+                Number => Ready<u32>);
+
+        let a: TestFut = ready(5u64).into();
+        assert_eq!(Some(5), a.clone().into_immediate().map(Ready::into_inner));
+        assert!(a.is_immediate());
+    }
+
+    #[test]
+    fn clone_bounds() {
+        fn is_clone<T: Clone>(_: &T) {}
+
+        // the type of the disabled variant is never named
+        ::union_future_std!(#[union_future(impl(Clone))] TestFut<T> where T: Unpin,
+                #[cfg(not(test))]
+                Missing => MissingFuture<T>,
+                Immediate => Ready<T>);
+
+        let a: TestFut<u64> = ready(5u64).into();
+        is_clone(&a);
+
+        // `Pending` is not `Clone`, which leaves the enum without a `Clone` impl
+        ::union_future_std!(#[union_future(impl(Clone))] OtherFut<u64>,
+                Forever => Pending<u64>,
+                Immediate => Ready<u64>);
+
+        let b: OtherFut = pending::<u64>().into();
+        assert!(b.is_forever());
+    }
+
+    #[test]
+    fn copy() {
+        ::union_future_std!(#[union_future(impl(Clone, Copy, Debug, PartialEq))] TestFut<u64>,
+                Big => Value<u64>,
+                Number => Value<u32>);
+
+        let a: TestFut = Value(5u64).into();
+        let b = a;
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn debug() {
+        ::union_future_std!(#[union_future(impl(Debug))] TestFut<u64>,
+                Forever => Pending<u64>,
+                Immediate => Ready<u64>);
+
+        let a: TestFut = ready(5u64).into();
+        assert_eq!("TestFut::Immediate(Ready(Some(5)))", format!("{:?}", a));
+        let b: TestFut = pending::<u64>().into();
+        assert_eq!("TestFut::Forever(Pending)", format!("{:?}", b));
+    }
+
+    #[test]
+    fn partial_eq() {
+        ::union_future_std!(#[union_future(impl(Debug, PartialEq))] TestFut<u64>,
+                Number => Value<u32>,
+                Big => Value<u64>);
+
+        let a: TestFut = Value(5u32).into();
+        assert_eq!(a, Value(5u32).into());
+        assert!(a != Value(6u32).into());
+        assert!(a != Value(5u64).into());
+    }
+
+    #[test]
+    fn hash() {
+        use std::collections::HashSet;
+
+        ::union_future_std!(#[union_future(impl(PartialEq, Eq, Hash))] TestFut<u64>,
+                Number => Value<u32>,
+                Big => Value<u64>);
+
+        let mut set = HashSet::<TestFut>::new();
+        assert!(set.insert(Value(5u32).into()));
+        assert!(set.insert(Value(5u64).into()));
+        assert!(!set.insert(Value(5u32).into()));
+        assert_eq!(2, set.len());
+    }
+
+    #[test]
+    fn ord() {
+        ::union_future_std!(
+            #[union_future(impl(Debug, PartialEq, Eq, PartialOrd, Ord))]
+            TestFut<u64>,
+                Big => Value<u64>,
+                Number => Value<u32>);
+
+        let mut values: Vec<TestFut> = vec![
+            Value(1u32).into(),
+            Value(7u64).into(),
+            Value(0u32).into(),
+            Value(5u64).into(),
+        ];
+        values.sort();
+        assert_eq!(vec![
+            TestFut::from(Value(5u64)),
+            TestFut::from(Value(7u64)),
+            TestFut::from(Value(0u32)),
+            TestFut::from(Value(1u32)),
+        ], values);
+        assert!(TestFut::from(Value(9u64)) < TestFut::from(Value(0u32)));
+
+        // `PartialOrd` does not require the variants to be `Ord`
+        ::union_future_std!(#[union_future(impl(PartialEq, PartialOrd))] FloatFut<f64>,
+                Small => Value<f32>,
+                Big => Value<f64>);
+
+        let nan = FloatFut::from(Value(f64::NAN));
+        assert_eq!(None, nan.partial_cmp(&nan));
+        assert!(FloatFut::from(Value(1.0f32)) < FloatFut::from(Value(0.0f64)));
+    }
+
+    #[test]
+    fn assert_send() {
+        ::union_future_std!(#[assert_send] TestFut<u64>,
+                Big => Value<u64>,
+                Number => Value<u32>);
+
+        ::union_future_std!(#[assert_send] GenericFut<T> where T: Send + Unpin + Clone,
+                Forever => Pending<T>,
+                Immediate => Value<T>);
+
+        let a: TestFut = Value(5u64).into();
+        assert!(a.is_big());
+    }
+
+    #[test]
+    fn assert_sync() {
+        ::union_future_std!(#[assert_sync] #[assert_send] TestFut<u64>,
+                Big => Value<u64>,
+                Number => Value<u32>);
+
+        ::union_future_std!(#[assert_sync] GenericFut<T> where T: Sync + Unpin + Clone,
+                Forever => Pending<T>,
+                Immediate => Value<T>);
+
+        let a: ::std::sync::Arc<TestFut> = ::std::sync::Arc::new(Value(5u64).into());
+        assert!(a.is_big());
+    }
+
+    #[test]
+    fn display() {
+        ::union_future_std!(#[union_future(impl(Display))] TestFut<u64>,
+                Number => Value<u32>,
+                Big => Value<u64>);
+
+        let a: TestFut = Value(5u32).into();
+        assert_eq!("value 5", a.to_string());
+    }
+
+    #[test]
+    fn hand_written_impls() {
+        ::union_future_std!(TestFut<u64>,
+                Number => Value<u32>,
+                Big => Value<u64>);
+
+        // the enum has no std trait impls unless they are requested, so these don't conflict
+        impl fmt::Debug for TestFut {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "TestFut({})", self.variant_name())
+            }
+        }
+
+        impl fmt::Display for TestFut {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.variant_name())
+            }
+        }
+
+        let a: TestFut = Value(5u32).into();
+        assert_eq!("TestFut(Number)", format!("{:?}", a));
+        assert_eq!("Number", a.to_string());
+    }
+
+    #[test]
+    fn derived_and_requested_impls() {
+        ::union_future_std!(
+            #[derive(Debug, Clone)]
+            #[union_future(impl(PartialEq, Display))]
+            TestFut<u64>,
+                Number => Value<u32>,
+                Big => Value<u64>);
+
+        let a: TestFut = Value(5u32).into();
+        assert_eq!(a, a.clone());
+        assert_eq!("Number(Value(5))", format!("{:?}", a));
+        assert_eq!("value 5", a.to_string());
+    }
+
+    #[test]
+    fn assert_unwind_safe() {
+        use std::cell::RefCell;
+        use std::panic::{self, RefUnwindSafe, UnwindSafe};
+        use std::rc::Rc;
+
+        pub struct Shared(Rc<RefCell<u64>>);
+
+        impl Future for Shared {
+            type Output = u64;
+
+            fn poll(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<u64> {
+                Poll::Ready(*self.0.borrow())
+            }
+        }
+
+        fn is_unwind_safe<T: UnwindSafe + RefUnwindSafe>(_: &T) {}
+
+        ::union_future_std!(TestFut<u64>,
+                #[assert_unwind_safe]
+                Cached => Shared,
+                Number => Ready<u64>);
+
+        let a: TestFut = Shared(Rc::new(RefCell::new(5))).into();
+        is_unwind_safe(&a);
+        assert!(panic::catch_unwind(move || a.is_cached()).unwrap());
+    }
+}
@@ -0,0 +1,64 @@
+//! Checks that a union future of 10 variants is expanded and type checked in a bounded time, so
+//! that an expansion which is quadratic in the number of variants is caught.
+
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+const VARIANTS: usize = 10;
+const THRESHOLD: Duration = Duration::from_secs(5);
+
+fn source() -> String {
+    let mut src = String::from("#[macro_use]\nextern crate union_future;\nextern crate futures;\n\n\
+                                use futures::{Async, Future, Poll};\n\n\
+                                pub struct Value<const N: u64>;\n\n\
+                                impl<const N: u64> Future for Value<N> {\n\
+                                \x20   type Item = u64;\n\
+                                \x20   type Error = ();\n\n\
+                                \x20   fn poll(&mut self) -> Poll<u64, ()> {\n\
+                                \x20       Ok(Async::Ready(N))\n\
+                                \x20   }\n\
+                                }\n\n\
+                                union_future!(TestFut<u64, ()>");
+    for i in 0..VARIANTS {
+        write!(src, ",\n        V{} => Value<{}>", i, i).unwrap();
+    }
+    src.push_str(");\n");
+    src
+}
+
+fn check(dir: &Path) -> Duration {
+    let start = Instant::now();
+    let output = Command::new(env!("CARGO"))
+        .args(["check", "--quiet"])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    let elapsed = start.elapsed();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    elapsed
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn ten_variants() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("compile_time");
+    fs::create_dir_all(dir.join("src")).unwrap();
+    let manifest = format!("[package]\nname = \"compile-time\"\nversion = \"0.0.0\"\n\n\
+                            [dependencies]\nfutures = \"0.1.7\"\n\
+                            union-future = {{ path = {:?} }}\n\n[workspace]\n",
+                           env!("CARGO_MANIFEST_DIR"));
+    fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+
+    // the first check builds the dependencies, and the second one only checks the crate
+    fs::write(dir.join("src/lib.rs"), source()).unwrap();
+    check(&dir);
+    fs::write(dir.join("src/lib.rs"), source() + "\n").unwrap();
+    let elapsed = check(&dir);
+    assert!(elapsed < THRESHOLD,
+            "checking a union future of {} variants took {:?}",
+            VARIANTS,
+            elapsed);
+}
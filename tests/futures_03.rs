@@ -0,0 +1,77 @@
+//! Expands the macros with only the `futures-03` feature enabled, where the futures 0.1 crate is
+//! not a dependency, so generated code naming it fails to compile here.
+
+#[macro_use]
+extern crate union_future;
+
+use std::future::{pending, ready, Future, Pending, Ready};
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+#[derive(PartialEq, Debug)]
+pub struct Error;
+
+union_future!(TestFut<u64, Error>,
+        Immediate => Ready<Result<u64, Error>>,
+        Forever => Pending<Result<u64, Error>>);
+
+union_future_std!(StdFut<u64>,
+        Immediate => Ready<u64>,
+        Forever => Pending<u64>);
+
+pub enum ImplFut {
+    Immediate(Ready<Result<u64, Error>>),
+    Forever(Pending<Result<u64, Error>>),
+}
+
+union_future_impl!(ImplFut<u64, Error>,
+        Immediate => Ready<Result<u64, Error>>,
+        Forever => Pending<Result<u64, Error>>);
+
+// the prelude names are shadowed where the macro is invoked
+mod hygiene {
+    #![allow(dead_code, non_upper_case_globals)]
+    pub struct Ok<T>(pub T);
+    pub struct Err<T>(pub T);
+    pub struct Some<T>(pub T);
+    pub const None: () = ();
+    pub trait From<T> {}
+    pub type Option<T> = Vec<T>;
+
+    union_future!(pub TestFut<u64, super::Error>,
+            Immediate => ::std::future::Ready<::std::result::Result<u64, super::Error>>);
+}
+
+fn poll<F: Future + Unpin>(f: &mut F) -> Poll<F::Output> {
+    Pin::new(f).poll(&mut Context::from_waker(Waker::noop()))
+}
+
+#[test]
+fn union_future() {
+    let mut a: TestFut = ready(Ok(5)).into();
+    assert_eq!(Poll::Ready(Ok(5)), poll(&mut a));
+    let mut b: TestFut = pending().into();
+    assert_eq!(Poll::Pending, poll(&mut b));
+    assert!(b.is_forever());
+}
+
+#[test]
+fn shadowed_names() {
+    let mut a: hygiene::TestFut = ready(Ok(5)).into();
+    assert_eq!(Poll::Ready(Ok(5)), poll(&mut a));
+    assert!(a.as_immediate().is_some());
+}
+
+#[test]
+fn union_future_std() {
+    let mut a: StdFut = ready(5).into();
+    assert_eq!(Poll::Ready(5), poll(&mut a));
+    let mut b: StdFut = pending().into();
+    assert_eq!(Poll::Pending, poll(&mut b));
+}
+
+#[test]
+fn union_future_impl() {
+    let mut a: ImplFut = ready(Err(Error)).into();
+    assert_eq!(Poll::Ready(Err(Error)), poll(&mut a));
+}
@@ -0,0 +1,325 @@
+//! Derive macro generating the futures 0.1 `Future` impl of an enum of futures.
+//!
+//! `#[derive(UnionFuture)]` is an alternative to the `union_future` macro of the `union-future`
+//! crate which works on a regular enum declaration. The item and error types of the future are
+//! given by the `#[future(item = ..., error = ...)]` attribute of the enum, and every variant is a
+//! tuple variant with a single future annotated with `#[future_variant]`.
+//!
+//! The generated impls name the futures crate re-exported by the `union-future` crate, which must
+//! be a dependency of the crate deriving them, while `futures` itself need not be.
+//!
+//! ```
+//! #[macro_use]
+//! extern crate union_future_derive;
+//! extern crate union_future;
+//! extern crate futures;
+//!
+//! use futures::future::*;
+//!
+//! pub struct DbError;
+//!
+//! #[derive(UnionFuture)]
+//! #[future(item = u64, error = DbError)]
+//! pub enum QueryFuture {
+//!     #[future_variant]
+//!     Cached(FutureResult<u64, DbError>),
+//!     #[future_variant]
+//!     Db(Empty<u64, DbError>),
+//! }
+//!
+//! fn query(cached: Option<u64>) -> QueryFuture {
+//!     match cached {
+//!         Some(n) => ok(n).into(),
+//!         None => empty().into(),
+//!     }
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! The items and errors of the variants are converted with the `From` trait, like with the
+//! `union_future` macro, and a `From` impl is generated for each variant unless it is annotated
+//! with `#[future_variant(no_from)]`. The errors for a variant that isn't a future, or whose item
+//! or error cannot be converted, point at the type of the variant.
+//!
+//! ```compile_fail
+//! # #[macro_use] extern crate union_future_derive;
+//! # extern crate union_future;
+//! # extern crate futures;
+//! # use futures::future::*;
+//! #[derive(UnionFuture)]
+//! #[future(item = u64, error = ())]
+//! pub enum QueryFuture {
+//!     #[future_variant]
+//!     Cached(FutureResult<u64, ()>),
+//!     // error: variant `Db` must be annotated with `#[future_variant]`
+//!     Db(Empty<u64, ()>),
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! The `#[union_future(Item = ..., Error = ...)]` attribute generates the same impls without the
+//! per-variant annotations.
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use syn::spanned::Spanned;
+use syn::{Attribute, Data, DeriveInput, Error, Fields, Ident, Result, Type};
+
+/// Derives the futures 0.1 `Future` trait and the `From` impls of an enum of futures.
+///
+/// See the crate documentation for the attributes.
+#[proc_macro_derive(UnionFuture, attributes(future, future_variant))]
+pub fn derive_union_future(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    expand(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+struct Variant<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    from: bool,
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => {
+            return Err(Error::new_spanned(&input.ident,
+                                          "UnionFuture can only be derived for enums"))
+        }
+    };
+    let (item, error) = parse_future_attr(input)?;
+    let variants = data.variants
+        .iter()
+        .map(|v| {
+            let from = parse_variant_attr(&v.attrs, &v.ident)?;
+            Ok(Variant { ident: &v.ident, ty: variant_type(v)?, from })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(impls(input, &item, &error, &variants))
+}
+
+/// Generates the futures 0.1 `Future` impl and the `From` impls of an existing enum of futures.
+///
+/// The item and error types are given as arguments of the attribute, and each variant is a tuple
+/// variant with a single future. A variant annotated with `#[no_from]` gets no `From` impl, like
+/// with the `union_future` macro.
+///
+/// ```
+/// extern crate union_future_derive;
+/// extern crate union_future;
+/// extern crate futures;
+///
+/// use futures::future::*;
+/// use union_future_derive::union_future;
+///
+/// pub struct DbError;
+///
+/// #[union_future(Item = u64, Error = DbError)]
+/// pub enum QueryFuture {
+///     Cached(FutureResult<u64, DbError>),
+///     #[no_from]
+///     Db(Empty<u64, DbError>),
+/// }
+///
+/// fn query(cached: Option<u64>) -> QueryFuture {
+///     match cached {
+///         Some(n) => ok(n).into(),
+///         None => QueryFuture::Db(empty()),
+///     }
+/// }
+/// # fn main() {}
+/// ```
+#[proc_macro_attribute]
+pub fn union_future(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut item = None;
+    let mut error = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("Item") {
+            item = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("Error") {
+            error = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `Item` or `Error`"))
+        }
+    });
+    syn::parse_macro_input!(args with parser);
+    let mut input = syn::parse_macro_input!(input as DeriveInput);
+    expand_attr(&mut input, item, error).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand_attr(input: &mut DeriveInput,
+               item: Option<Type>,
+               error: Option<Type>)
+               -> Result<TokenStream2> {
+    let (item, error) = match (item, error) {
+        (Some(item), Some(error)) => (item, error),
+        (None, _) => {
+            return Err(Error::new_spanned(&input.ident,
+                                          "missing the item type, such as \
+                                           `#[union_future(Item = u64, ...)]`"))
+        }
+        (_, None) => {
+            return Err(Error::new_spanned(&input.ident,
+                                          "missing the error type, such as \
+                                           `#[union_future(Error = MyError, ...)]`"))
+        }
+    };
+    let impls = match input.data {
+        Data::Enum(ref data) => {
+            let variants = data.variants
+                .iter()
+                .map(|v| {
+                    let from = !v.attrs.iter().any(|a| a.path().is_ident("no_from"));
+                    Ok(Variant { ident: &v.ident, ty: variant_type(v)?, from })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            impls(input, &item, &error, &variants)
+        }
+        _ => {
+            return Err(Error::new_spanned(&input.ident,
+                                          "union_future can only be applied to enums"))
+        }
+    };
+    // `#[no_from]` is not a real attribute, so it is removed from the enum
+    if let Data::Enum(ref mut data) = input.data {
+        for v in data.variants.iter_mut() {
+            v.attrs.retain(|a| !a.path().is_ident("no_from"));
+        }
+    }
+    Ok(quote! {
+        #input
+        #impls
+    })
+}
+
+fn variant_type(v: &syn::Variant) -> Result<&Type> {
+    match v.fields {
+        Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => Ok(&fields.unnamed[0].ty),
+        _ => {
+            Err(Error::new_spanned(v,
+                                   format!("variant `{}` must be a tuple variant with a single \
+                                            future",
+                                           v.ident)))
+        }
+    }
+}
+
+fn impls(input: &DeriveInput, item: &Type, error: &Type, variants: &[Variant]) -> TokenStream2 {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // the futures crate re-exported by union_future, which is the one its macros use, so that the
+    // crate deriving the impls need not depend on futures under that name
+    let futures = quote!(::union_future::__futures);
+    let arms = variants.iter().map(|v| {
+        let ident = v.ident;
+        // spanned at the type of the variant, so that a missing impl is reported there
+        let poll = quote_spanned!(v.ty.span()=> #futures::Future::poll(f));
+        let from_item = quote_spanned!(v.ty.span()=> ::std::convert::From::from(t));
+        let from_error = quote_spanned!(v.ty.span()=> ::std::convert::From::from(e));
+        quote! {
+            #name::#ident(ref mut f) => {
+                match #poll {
+                    ::std::result::Result::Ok(#futures::Async::Ready(t)) => {
+                        ::std::result::Result::Ok(#futures::Async::Ready(#from_item))
+                    }
+                    ::std::result::Result::Ok(#futures::Async::NotReady) => {
+                        ::std::result::Result::Ok(#futures::Async::NotReady)
+                    }
+                    ::std::result::Result::Err(e) => ::std::result::Result::Err(#from_error),
+                }
+            }
+        }
+    });
+    let froms = variants.iter().filter(|v| v.from).map(|v| {
+        let ident = v.ident;
+        let ty = v.ty;
+        quote! {
+            impl #impl_generics ::std::convert::From<#ty> for #name #ty_generics #where_clause {
+                fn from(other: #ty) -> Self {
+                    #name::#ident(other)
+                }
+            }
+        }
+    });
+
+    quote! {
+        impl #impl_generics #futures::Future for #name #ty_generics #where_clause {
+            type Item = #item;
+            type Error = #error;
+
+            #[allow(clippy::useless_conversion)]
+            fn poll(&mut self) -> #futures::Poll<Self::Item, Self::Error> {
+                match *self {
+                    #(#arms)*
+                }
+            }
+        }
+
+        #(#froms)*
+    }
+}
+
+// parses `#[future(item = ..., error = ...)]`
+fn parse_future_attr(input: &DeriveInput) -> Result<(Type, Type)> {
+    let mut item = None;
+    let mut error = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("future")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("item") {
+                item = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("error") {
+                error = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `item` or `error`"))
+            }
+        })?;
+    }
+    match (item, error) {
+        (Some(item), Some(error)) => Ok((item, error)),
+        (None, _) => {
+            Err(Error::new_spanned(&input.ident,
+                                   "missing the item type, such as `#[future(item = u64, ...)]`"))
+        }
+        (_, None) => {
+            Err(Error::new_spanned(&input.ident,
+                                   "missing the error type, such as `#[future(error = MyError, \
+                                    ...)]`"))
+        }
+    }
+}
+
+// parses `#[future_variant]`, returning whether the `From` impl is generated
+fn parse_variant_attr(attrs: &[Attribute], ident: &Ident) -> Result<bool> {
+    let attr = match attrs.iter().find(|a| a.path().is_ident("future_variant")) {
+        Some(attr) => attr,
+        None => {
+            return Err(Error::new_spanned(ident,
+                                          format!("variant `{}` must be annotated with \
+                                                   `#[future_variant]`",
+                                                  ident)))
+        }
+    };
+    let mut from = true;
+    if let syn::Meta::List(_) = attr.meta {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("no_from") {
+                from = false;
+                Ok(())
+            } else {
+                Err(meta.error("expected `no_from`"))
+            }
+        })?;
+    }
+    Ok(from)
+}
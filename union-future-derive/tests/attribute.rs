@@ -0,0 +1,70 @@
+extern crate union_future_derive;
+extern crate union_future;
+extern crate futures;
+
+use futures::future::*;
+use futures::Async;
+use union_future_derive::union_future;
+
+#[derive(Debug, PartialEq)]
+pub struct DbError(u32);
+
+#[derive(Debug, PartialEq)]
+pub struct IoError;
+
+impl From<IoError> for DbError {
+    fn from(_: IoError) -> DbError {
+        DbError(0)
+    }
+}
+
+#[union_future(Item = u64, Error = DbError)]
+#[derive(Debug)]
+pub enum QueryFuture {
+    Cached(FutureResult<u64, DbError>),
+    Io(FutureResult<u32, IoError>),
+    #[no_from]
+    Pending(Empty<u64, DbError>),
+}
+
+impl From<Empty<u64, DbError>> for QueryFuture {
+    fn from(_: Empty<u64, DbError>) -> QueryFuture {
+        QueryFuture::Cached(ok(0))
+    }
+}
+
+#[union_future(Item = T, Error = E)]
+pub enum Generic<T, E, F>
+    where F: Future<Item = T, Error = E>
+{
+    #[no_from]
+    Ready(FutureResult<T, E>),
+    Other(F),
+}
+
+#[test]
+fn poll() {
+    let mut f: QueryFuture = ok::<u64, _>(5).into();
+    assert_eq!(Ok(Async::Ready(5)), f.poll());
+
+    let mut f: QueryFuture = err::<u32, _>(IoError).into();
+    assert_eq!(Err(DbError(0)), f.poll());
+
+    let mut f = QueryFuture::Pending(empty());
+    assert_eq!(Ok(Async::NotReady), f.poll());
+}
+
+#[test]
+fn keeps_derives() {
+    let f: QueryFuture = ok::<u64, _>(5).into();
+    assert!(format!("{:?}", f).starts_with("Cached("));
+}
+
+#[test]
+fn generics() {
+    let mut f: Generic<u64, DbError, Empty<u64, DbError>> = empty().into();
+    assert_eq!(Ok(Async::NotReady), f.poll());
+
+    let mut f: Generic<u64, DbError, Empty<u64, DbError>> = Generic::Ready(ok(1));
+    assert_eq!(Ok(Async::Ready(1)), f.poll());
+}
@@ -0,0 +1,79 @@
+#[macro_use]
+extern crate union_future_derive;
+extern crate union_future;
+extern crate futures;
+
+use futures::future::*;
+use futures::Async;
+
+#[derive(Debug, PartialEq)]
+pub struct DbError(u32);
+
+#[derive(Debug, PartialEq)]
+pub struct IoError;
+
+impl From<IoError> for DbError {
+    fn from(_: IoError) -> DbError {
+        DbError(0)
+    }
+}
+
+#[derive(UnionFuture)]
+#[future(item = u64, error = DbError)]
+pub enum QueryFuture {
+    #[future_variant]
+    Cached(FutureResult<u64, DbError>),
+    #[future_variant]
+    Io(FutureResult<u32, IoError>),
+    // no From impl, so that `QueryFuture` doesn't implement `From<Empty<u64, DbError>>`
+    #[future_variant(no_from)]
+    Pending(Empty<u64, DbError>),
+}
+
+impl From<Empty<u64, DbError>> for QueryFuture {
+    fn from(_: Empty<u64, DbError>) -> QueryFuture {
+        QueryFuture::Cached(ok(0))
+    }
+}
+
+#[derive(UnionFuture)]
+#[future(item = T, error = E)]
+pub enum Generic<T, E, F>
+    where F: Future<Item = T, Error = E>
+{
+    // `From<FutureResult<T, E>>` would overlap with `From<F>`
+    #[future_variant(no_from)]
+    Ready(FutureResult<T, E>),
+    #[future_variant]
+    Other(F),
+}
+
+#[test]
+fn poll() {
+    let mut f: QueryFuture = ok::<u64, _>(5).into();
+    assert_eq!(Ok(Async::Ready(5)), f.poll());
+
+    let mut f: QueryFuture = err::<u64, _>(DbError(3)).into();
+    assert_eq!(Err(DbError(3)), f.poll());
+
+    let mut f = QueryFuture::Pending(empty());
+    assert_eq!(Ok(Async::NotReady), f.poll());
+}
+
+#[test]
+fn converts_item_and_error() {
+    let mut f: QueryFuture = ok::<u32, IoError>(7).into();
+    assert_eq!(Ok(Async::Ready(7)), f.poll());
+
+    let mut f: QueryFuture = err::<u32, _>(IoError).into();
+    assert_eq!(Err(DbError(0)), f.poll());
+}
+
+#[test]
+fn generics() {
+    let mut f: Generic<u64, DbError, Empty<u64, DbError>> = empty().into();
+    assert_eq!(Ok(Async::NotReady), f.poll());
+
+    let mut f: Generic<u64, DbError, Empty<u64, DbError>> = Generic::Ready(ok(1));
+    assert_eq!(Ok(Async::Ready(1)), f.poll());
+}
@@ -0,0 +1,46 @@
+//! The derived impls go through the futures crate re-exported by union_future, so they compile in
+//! a crate which doesn't name the futures crate `futures`.
+
+#[macro_use]
+extern crate union_future_derive;
+extern crate union_future;
+extern crate futures as futures01;
+
+use futures01::future::*;
+use futures01::{Async, Future};
+use union_future_derive::union_future;
+
+// a module named like the futures crate, which the derived impls must not use
+#[allow(dead_code)]
+mod futures {}
+
+#[derive(UnionFuture)]
+#[future(item = u64, error = ())]
+pub enum DerivedFuture {
+    #[future_variant]
+    Cached(FutureResult<u64, ()>),
+    #[future_variant]
+    Pending(Empty<u64, ()>),
+}
+
+#[union_future(Item = u64, Error = ())]
+pub enum AttributeFuture {
+    Cached(FutureResult<u64, ()>),
+    Pending(Empty<u64, ()>),
+}
+
+#[test]
+fn derive() {
+    let mut a: DerivedFuture = ok(5).into();
+    assert_eq!(Ok(Async::Ready(5)), a.poll());
+    let mut b: DerivedFuture = empty().into();
+    assert_eq!(Ok(Async::NotReady), b.poll());
+}
+
+#[test]
+fn attribute() {
+    let mut a: AttributeFuture = ok(5).into();
+    assert_eq!(Ok(Async::Ready(5)), a.poll());
+    let mut b: AttributeFuture = empty().into();
+    assert_eq!(Ok(Async::NotReady), b.poll());
+}
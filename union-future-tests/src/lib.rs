@@ -0,0 +1,3 @@
+//! The tests of this crate use the combinators of futures 0.3 with the enums of `union-future`.
+//! They live in a separate crate since `union-future` uses the 2015 edition, which has no async
+//! blocks.
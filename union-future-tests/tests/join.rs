@@ -0,0 +1,65 @@
+use futures::executor::block_on;
+use futures::future::{join_all, lazy, try_join_all, Lazy};
+use futures::{join, try_join};
+use std::future::{ready, Ready};
+use std::task::Context;
+use union_future::{union_future_03, union_future_std};
+
+#[derive(PartialEq, Debug)]
+pub enum Error {
+    Fail,
+}
+
+pub type LazyValue = Lazy<fn(&mut Context<'_>) -> Result<u64, Error>>;
+
+union_future_03!(ValueFuture<u64, Error>,
+        Ready => Ready<Result<u64, Error>>,
+        Lazy => LazyValue);
+
+union_future_std!(NameFuture<String>,
+        Static => Ready<&'static str>,
+        Owned => Ready<String>);
+
+fn assert_send<T: Send>(t: T) -> T {
+    t
+}
+
+fn lazy_value(_: &mut Context<'_>) -> Result<u64, Error> {
+    Ok(2)
+}
+
+#[test]
+fn join() {
+    let a: ValueFuture = ready(Ok(1)).into();
+    let b: ValueFuture = lazy(lazy_value as fn(&mut Context<'_>) -> _).into();
+    let c: NameFuture = ready("c").into();
+    let d: NameFuture = ready("d".to_owned()).into();
+    let joined = assert_send(async { join!(a, b, c, d) });
+    assert_eq!((Ok(1), Ok(2), "c".to_owned(), "d".to_owned()), block_on(joined));
+}
+
+#[test]
+fn try_join() {
+    let a: ValueFuture = ready(Ok(1)).into();
+    let b: ValueFuture = lazy(lazy_value as fn(&mut Context<'_>) -> _).into();
+    let joined = assert_send(async { try_join!(a, b) });
+    assert_eq!(Ok((1, 2)), block_on(joined));
+
+    let a: ValueFuture = lazy(lazy_value as fn(&mut Context<'_>) -> _).into();
+    let b: ValueFuture = ready(Err(Error::Fail)).into();
+    assert_eq!(Err(Error::Fail), block_on(async { try_join!(a, b) }));
+}
+
+#[test]
+fn join_all_vec() {
+    let futures: Vec<ValueFuture> = vec![ready(Ok(1)).into(),
+                                         lazy(lazy_value as fn(&mut Context<'_>) -> _).into(),
+                                         ready(Ok(3)).into()];
+    assert_eq!(vec![Ok(1), Ok(2), Ok(3)], block_on(join_all(futures)));
+
+    let futures: Vec<NameFuture> = vec![ready("a").into(), ready("b".to_owned()).into()];
+    assert_eq!(vec!["a".to_owned(), "b".to_owned()], block_on(join_all(futures)));
+
+    let futures: Vec<ValueFuture> = vec![ready(Ok(1)).into(), ready(Err(Error::Fail)).into()];
+    assert_eq!(Err(Error::Fail), block_on(try_join_all(futures)));
+}
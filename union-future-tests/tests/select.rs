@@ -0,0 +1,71 @@
+use futures::executor::block_on;
+use futures::future::{self, pending, ready, FusedFuture, FutureExt};
+use futures::{select, select_biased};
+use union_future::union_future_03;
+
+// the futures of the futures crate are fused, so the enum is fused as well
+union_future_03!(FusedValue<u64, ()>,
+        Ready => future::Ready<Result<u64, ()>>,
+        Pending => future::Pending<Result<u64, ()>>);
+
+// `std::future::Ready` is not fused, so the enum is fused with `FutureExt::fuse`
+union_future_03!(StdValue<u64, ()>,
+        Ready => std::future::Ready<Result<u64, ()>>,
+        Pending => std::future::Pending<Result<u64, ()>>);
+
+#[test]
+fn select_fused() {
+    let mut a: FusedValue = pending().into();
+    let mut b: FusedValue = ready(Ok(2)).into();
+    let selected = block_on(async {
+        select! {
+            a = a => a.map(|a| ("a", a)),
+            b = b => b.map(|b| ("b", b)),
+        }
+    });
+    assert_eq!(Ok(("b", 2)), selected);
+    assert!(b.is_terminated());
+}
+
+#[test]
+fn select_fuse() {
+    let a: StdValue = std::future::pending().into();
+    let b: StdValue = std::future::ready(Ok(2)).into();
+    let (mut a, mut b) = (a.fuse(), b.fuse());
+    let selected = block_on(async {
+        select! {
+            a = a => a.map(|a| ("a", a)),
+            b = b => b.map(|b| ("b", b)),
+        }
+    });
+    assert_eq!(Ok(("b", 2)), selected);
+    assert!(b.is_terminated());
+}
+
+#[test]
+fn select_biased() {
+    // both futures are ready, so the first branch is always taken
+    for _ in 0..10 {
+        let mut a: FusedValue = ready(Ok(1)).into();
+        let mut b: FusedValue = ready(Ok(2)).into();
+        let selected = block_on(async {
+            select_biased! {
+                a = a => a.map(|a| ("a", a)),
+                b = b => b.map(|b| ("b", b)),
+            }
+        });
+        assert_eq!(Ok(("a", 1)), selected);
+        assert!(a.is_terminated());
+        assert!(!b.is_terminated());
+    }
+
+    let mut a: FusedValue = pending().into();
+    let mut b: FusedValue = ready(Ok(2)).into();
+    let selected = block_on(async {
+        select_biased! {
+            a = a => a.map(|a| ("a", a)),
+            b = b => b.map(|b| ("b", b)),
+        }
+    });
+    assert_eq!(Ok(("b", 2)), selected);
+}